@@ -0,0 +1,198 @@
+use anyhow::Context;
+use std::{path::Path, str::FromStr, time::Duration};
+
+/// When a rule applies, counted in messages received by the instrument.
+#[derive(Debug, Clone, Copy)]
+enum Trigger {
+    /// Only the Nth message.
+    At(u64),
+    /// Every Nth message.
+    Every(u64),
+}
+
+impl Trigger {
+    fn matches(self, n: u64) -> bool {
+        match self {
+            Trigger::At(at) => n == at,
+            Trigger::Every(every) => n.is_multiple_of(every),
+        }
+    }
+}
+
+/// Misbehaviour injected when a rule applies.
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Delay the response.
+    Delay(Duration),
+    /// Close the connection without responding.
+    Drop,
+    /// Send a garbage line before the response.
+    Garbage,
+    /// Execute the message, but do not respond.
+    Ignore,
+    /// Push an entry to the error queue.
+    Error(i16, String),
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    trigger: Trigger,
+    action: Action,
+}
+
+/// Scripted faults.
+///
+/// Faults are described with one rule per line, empty lines and lines
+/// beginning with `#` are ignored:
+///
+/// ```text
+/// # respond to the 10th message after 2 seconds
+/// at 10 delay 2000
+/// every 25 garbage
+/// at 40 drop
+/// every 7 ignore
+/// every 50 error -350 Queue overflow
+/// # reject channel switches less than 20ms apart
+/// nsel-guard 20
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Faults {
+    rules: Vec<Rule>,
+    /// Minimum time between `:INST:NSEL` commands.
+    pub nsel_guard: Option<Duration>,
+}
+
+fn parse_num<T: FromStr>(s: Option<&str>, what: &str) -> anyhow::Result<T> {
+    let s: &str = s.with_context(|| format!("Missing {what}"))?;
+    s.parse()
+        .map_err(|_| anyhow::anyhow!("Invalid {what}: {s}"))
+}
+
+fn parse_rule(line: &str) -> anyhow::Result<Option<Rule>> {
+    let mut words = line.split_whitespace();
+    let trigger: Trigger = match words.next() {
+        Some("at") => Trigger::At(parse_num(words.next(), "message number")?),
+        Some("every") => match parse_num(words.next(), "message interval")? {
+            0 => anyhow::bail!("Message interval must be greater than zero"),
+            every => Trigger::Every(every),
+        },
+        Some(word) => anyhow::bail!("Unknown trigger: {word}"),
+        None => return Ok(None),
+    };
+    let action: Action = match words.next() {
+        Some("delay") => Action::Delay(Duration::from_millis(parse_num(words.next(), "delay")?)),
+        Some("drop") => Action::Drop,
+        Some("garbage") => Action::Garbage,
+        Some("ignore") => Action::Ignore,
+        Some("error") => {
+            let code: i16 = parse_num(words.next(), "error code")?;
+            let message: String = words.collect::<Vec<&str>>().join(" ");
+            return Ok(Some(Rule {
+                trigger,
+                action: Action::Error(code, message),
+            }));
+        }
+        Some(word) => anyhow::bail!("Unknown action: {word}"),
+        None => anyhow::bail!("Missing action"),
+    };
+    if let Some(word) = words.next() {
+        anyhow::bail!("Unexpected argument: {word}");
+    }
+    Ok(Some(Rule { trigger, action }))
+}
+
+impl FromStr for Faults {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut faults: Faults = Faults::default();
+        for (idx, line) in s.lines().enumerate() {
+            let line: &str = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+            let result: anyhow::Result<()> = if let Some(ms) = line.strip_prefix("nsel-guard") {
+                parse_num(Some(ms.trim()), "guard time")
+                    .map(|ms| faults.nsel_guard = Some(Duration::from_millis(ms)))
+            } else {
+                parse_rule(line).map(|rule| faults.rules.extend(rule))
+            };
+            result.with_context(|| format!("Invalid fault on line {}", idx + 1))?;
+        }
+        Ok(faults)
+    }
+}
+
+impl Faults {
+    /// Load faults from a file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?
+            .parse()
+    }
+
+    /// Actions for the Nth message received, starting at 1.
+    pub fn actions(&self, n: u64) -> impl Iterator<Item = &Action> {
+        self.rules
+            .iter()
+            .filter(move |rule| rule.trigger.matches(n))
+            .map(|rule| &rule.action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_script() {
+        let faults: Faults = "# comment\n\
+                              \n\
+                              at 10 delay 2000\n\
+                              every 25 garbage\n\
+                              at 40 drop\n\
+                              every 7 ignore\n\
+                              every 50 error -350 Queue overflow\n\
+                              nsel-guard 20\n"
+            .parse()
+            .unwrap();
+        assert_eq!(faults.rules.len(), 5);
+        assert_eq!(faults.nsel_guard, Some(Duration::from_millis(20)));
+
+        let actions: Vec<&Action> = faults.actions(10).collect();
+        assert!(matches!(actions.as_slice(), [Action::Delay(d)] if *d == Duration::from_secs(2)));
+        assert!(matches!(faults.actions(40).next(), Some(Action::Drop)));
+        assert!(matches!(faults.actions(14).next(), Some(Action::Ignore)));
+        assert_eq!(faults.actions(1).count(), 0);
+
+        let actions: Vec<&Action> = faults.actions(50).collect();
+        assert!(matches!(
+            actions.as_slice(),
+            [Action::Garbage, Action::Error(-350, msg)] if msg == "Queue overflow"
+        ));
+    }
+
+    #[test]
+    fn parse_errors() {
+        for script in [
+            "at",
+            "at x drop",
+            "every 0 drop",
+            "sometimes 5 drop",
+            "at 5",
+            "at 5 explode",
+            "at 5 drop now",
+            "at 5 delay",
+            "every 5 error",
+            "nsel-guard",
+        ] {
+            assert!(script.parse::<Faults>().is_err(), "{script}");
+        }
+    }
+
+    #[test]
+    fn error_line_number() {
+        let err: anyhow::Error = "at 1 drop\n\nat 2 explode".parse::<Faults>().unwrap_err();
+        assert_eq!(err.to_string(), "Invalid fault on line 3");
+    }
+}
@@ -0,0 +1,446 @@
+use crate::scpi::Command;
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+const NUM_CH: usize = 3;
+
+/// Maximum number of entries in the error queue.
+const ERROR_QUEUE_LEN: usize = 16;
+
+/// Channel ratings of the DP832 in volts and amps.
+const RATINGS: [(f32, f32); NUM_CH] = [(30.0, 3.0), (30.0, 3.0), (5.0, 3.0)];
+
+/// Setting ranges are slightly above the ratings.
+const RANGE_FACTOR: f32 = 1.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    ConstantVoltage,
+    ConstantCurrent,
+}
+
+#[derive(Debug)]
+struct Channel {
+    voltage: f32,
+    current: f32,
+    ovp: f32,
+    ovp_on: bool,
+    ovp_tripped: bool,
+    ocp: f32,
+    ocp_on: bool,
+    ocp_tripped: bool,
+    output: bool,
+    tracking: bool,
+    /// Resistance of the simulated load in ohms.
+    load: f32,
+}
+
+impl Channel {
+    fn new(idx: usize, load: f32) -> Self {
+        let (max_voltage, max_current) = RATINGS[idx];
+        Self {
+            voltage: 0.0,
+            current: max_current,
+            ovp: max_voltage * RANGE_FACTOR,
+            ovp_on: false,
+            ovp_tripped: false,
+            ocp: max_current * RANGE_FACTOR,
+            ocp_on: false,
+            ocp_tripped: false,
+            output: false,
+            tracking: false,
+            load,
+        }
+    }
+
+    /// Output voltage, current, and regulation mode with the resistive load.
+    fn operating_point(&self) -> (f32, f32, Mode) {
+        if !self.output {
+            return (0.0, 0.0, Mode::ConstantVoltage);
+        }
+        if !self.load.is_finite() || self.voltage / self.load <= self.current {
+            let current: f32 = if self.load.is_finite() {
+                self.voltage / self.load
+            } else {
+                0.0
+            };
+            (self.voltage, current, Mode::ConstantVoltage)
+        } else {
+            // the load limits the voltage below the setpoint
+            (
+                self.current * self.load,
+                self.current,
+                Mode::ConstantCurrent,
+            )
+        }
+    }
+
+    /// Trip the protection if the output exceeds the protection values.
+    fn check_protection(&mut self) {
+        let (voltage, current, _) = self.operating_point();
+        if self.ovp_on && voltage >= self.ovp {
+            self.ovp_tripped = true;
+            self.output = false;
+        }
+        if self.ocp_on && current >= self.ocp {
+            self.ocp_tripped = true;
+            self.output = false;
+        }
+    }
+}
+
+/// SCPI error.
+struct Error(i16, Cow<'static, str>);
+
+const UNDEFINED_HEADER: Error = Error(-113, Cow::Borrowed("Undefined header"));
+const MISSING_PARAMETER: Error = Error(-109, Cow::Borrowed("Missing parameter"));
+const ILLEGAL_PARAMETER: Error = Error(-224, Cow::Borrowed("Illegal parameter value"));
+const OUT_OF_RANGE: Error = Error(-222, Cow::Borrowed("Data out of range"));
+const INVALID_COMMAND: Error = Error(-100, Cow::Borrowed("Command error"));
+
+fn on_off(b: bool) -> String {
+    if b { "ON" } else { "OFF" }.to_string()
+}
+
+fn parse_on_off(s: &str) -> Result<bool, Error> {
+    match s {
+        "ON" | "1" => Ok(true),
+        "OFF" | "0" => Ok(false),
+        _ => Err(ILLEGAL_PARAMETER),
+    }
+}
+
+fn parse_value(s: &str, max: f32) -> Result<f32, Error> {
+    let value: f32 = s.parse().map_err(|_| ILLEGAL_PARAMETER)?;
+    if !(0.0..=max).contains(&value) {
+        return Err(OUT_OF_RANGE);
+    }
+    Ok(value)
+}
+
+/// Simulated DP832 power supply.
+pub struct Instrument {
+    channels: Vec<Channel>,
+    selected: usize,
+    track_sync: bool,
+    errors: VecDeque<Error>,
+    nsel_guard: Option<Duration>,
+    last_nsel: Option<Instant>,
+}
+
+impl Instrument {
+    /// Create an instrument with a resistive load on each channel.
+    pub fn new(loads: [f32; NUM_CH]) -> Self {
+        Self {
+            channels: loads
+                .iter()
+                .enumerate()
+                .map(|(idx, load)| Channel::new(idx, *load))
+                .collect(),
+            selected: 0,
+            track_sync: true,
+            errors: VecDeque::new(),
+            nsel_guard: None,
+            last_nsel: None,
+        }
+    }
+
+    /// Reject `:INST:NSEL` commands received less than `guard` after the
+    /// previous one, as the DP832 does after rapid channel switches.
+    pub fn set_nsel_guard(&mut self, guard: Option<Duration>) {
+        self.nsel_guard = guard;
+    }
+
+    /// Push an entry to the error queue.
+    pub fn push_error_code(&mut self, code: i16, message: String) {
+        self.push_error(Error(code, Cow::Owned(message)))
+    }
+
+    /// Execute a message, returning the response if any.
+    pub fn execute(&mut self, msg: &str) -> Option<String> {
+        let mut responses: Vec<String> = Vec::new();
+        for cmd in crate::scpi::split_message(msg) {
+            let Some(cmd) = Command::parse(cmd) else {
+                continue;
+            };
+            match self.command(&cmd) {
+                Ok(Some(response)) => responses.push(response),
+                Ok(None) => (),
+                Err(e) => self.push_error(e),
+            }
+        }
+        for ch in self.channels.iter_mut() {
+            ch.check_protection();
+        }
+        (!responses.is_empty()).then(|| responses.join(";"))
+    }
+
+    fn push_error(&mut self, e: Error) {
+        if self.errors.len() < ERROR_QUEUE_LEN {
+            self.errors.push_back(e);
+        }
+    }
+
+    /// Channel index from a `CHn` parameter, or the selected channel.
+    fn ch_param(&self, cmd: &Command, idx: usize) -> Result<usize, Error> {
+        match cmd.params.get(idx) {
+            Some(p) => match p.strip_prefix("CH").and_then(|n| n.parse::<usize>().ok()) {
+                Some(n @ 1..=NUM_CH) => Ok(n - 1),
+                _ => Err(ILLEGAL_PARAMETER),
+            },
+            None => Ok(self.selected),
+        }
+    }
+
+    /// Channel index from the header suffix, or the selected channel.
+    fn ch_suffix(&self, cmd: &Command) -> Result<usize, Error> {
+        match cmd.suffix.map(usize::from) {
+            Some(n @ 1..=NUM_CH) => Ok(n - 1),
+            Some(_) => Err(UNDEFINED_HEADER),
+            None => Ok(self.selected),
+        }
+    }
+
+    /// Channel index and value parameter for `[CHn,]<value>` commands.
+    fn ch_value_params<'a>(&self, cmd: &'a Command) -> Result<(usize, &'a str), Error> {
+        match cmd.params.as_slice() {
+            [value] => Ok((self.selected, value)),
+            [_, value] => Ok((self.ch_param(cmd, 0)?, value)),
+            _ => Err(MISSING_PARAMETER),
+        }
+    }
+
+    /// Channel that tracks changes to `idx`, if any.
+    fn tracking_partner(&self, idx: usize) -> Option<usize> {
+        let partner: usize = match idx {
+            0 => 1,
+            1 => 0,
+            _ => return None,
+        };
+        (self.track_sync && self.channels[idx].tracking).then_some(partner)
+    }
+
+    fn command(&mut self, cmd: &Command) -> Result<Option<String>, Error> {
+        let path: Vec<&str> = cmd.path.iter().map(String::as_str).collect();
+        let response: Option<String> = match (path.as_slice(), cmd.query) {
+            (["*IDN"], true) => Some("RIGOL TECHNOLOGIES,DP832,DP8SIM0000000,00.01.16".to_string()),
+            (["*OPC"], true) => Some("1".to_string()),
+            (["*CLS"], false) => {
+                self.errors.clear();
+                None
+            }
+            (["*RST"], false) => {
+                let loads: Vec<f32> = self.channels.iter().map(|ch| ch.load).collect();
+                for (idx, ch) in self.channels.iter_mut().enumerate() {
+                    *ch = Channel::new(idx, loads[idx]);
+                }
+                self.selected = 0;
+                None
+            }
+            (["INST", "NSEL"], true) => Some((self.selected + 1).to_string()),
+            (["INST", "NSEL"], false) => {
+                let now: Instant = Instant::now();
+                let last: Option<Instant> = self.last_nsel.replace(now);
+                if let (Some(guard), Some(last)) = (self.nsel_guard, last)
+                    && now.duration_since(last) < guard
+                {
+                    return Err(INVALID_COMMAND);
+                }
+                let ch: &str = cmd.params.first().ok_or(MISSING_PARAMETER)?;
+                self.selected = match ch.parse::<usize>() {
+                    Ok(n @ 1..=NUM_CH) => n - 1,
+                    _ => return Err(ILLEGAL_PARAMETER),
+                };
+                None
+            }
+            (["SOUR", "VOLT"], true) => {
+                let idx: usize = self.ch_suffix(cmd)?;
+                Some(format!("{:.3}", self.channels[idx].voltage))
+            }
+            (["SOUR", "VOLT"], false) => {
+                let idx: usize = self.ch_suffix(cmd)?;
+                let value: &str = cmd.params.first().ok_or(MISSING_PARAMETER)?;
+                let value: f32 = parse_value(value, RATINGS[idx].0 * RANGE_FACTOR)?;
+                self.channels[idx].voltage = value;
+                if let Some(partner) = self.tracking_partner(idx) {
+                    self.channels[partner].voltage = value;
+                }
+                None
+            }
+            (["SOUR", "CURR"], true) => {
+                let idx: usize = self.ch_suffix(cmd)?;
+                Some(format!("{:.3}", self.channels[idx].current))
+            }
+            (["SOUR", "CURR"], false) => {
+                let idx: usize = self.ch_suffix(cmd)?;
+                let value: &str = cmd.params.first().ok_or(MISSING_PARAMETER)?;
+                let value: f32 = parse_value(value, RATINGS[idx].1 * RANGE_FACTOR)?;
+                self.channels[idx].current = value;
+                if let Some(partner) = self.tracking_partner(idx) {
+                    self.channels[partner].current = value;
+                }
+                None
+            }
+            (["OUTP"], true) => {
+                let idx: usize = self.ch_param(cmd, 0)?;
+                Some(on_off(self.channels[idx].output))
+            }
+            (["OUTP"], false) => {
+                let (idx, value) = self.ch_value_params(cmd)?;
+                let on: bool = parse_on_off(value)?;
+                let ch: &mut Channel = &mut self.channels[idx];
+                if on && (ch.ovp_tripped || ch.ocp_tripped) {
+                    // protection must be cleared first
+                    return Ok(None);
+                }
+                ch.output = on;
+                None
+            }
+            (["OUTP", "MODE"], true) => {
+                let idx: usize = self.ch_param(cmd, 0)?;
+                let (_, _, mode) = self.channels[idx].operating_point();
+                let mode: &str = match mode {
+                    Mode::ConstantVoltage => "CV",
+                    Mode::ConstantCurrent => "CC",
+                };
+                Some(mode.to_string())
+            }
+            (["OUTP", "TRAC"], true) => {
+                let idx: usize = self.ch_param(cmd, 0)?;
+                Some(on_off(self.channels[idx].tracking))
+            }
+            (["OUTP", "TRAC"], false) => {
+                let (idx, value) = self.ch_value_params(cmd)?;
+                if idx > 1 {
+                    return Err(ILLEGAL_PARAMETER);
+                }
+                let on: bool = parse_on_off(value)?;
+                self.channels[0].tracking = on;
+                self.channels[1].tracking = on;
+                None
+            }
+            (["OUTP", prot @ ("OVP" | "OCP"), rest @ ..], query) => {
+                let ovp: bool = *prot == "OVP";
+                match (rest, query) {
+                    (["VAL"], true) => {
+                        let ch: &Channel = &self.channels[self.ch_param(cmd, 0)?];
+                        Some(format!("{:.3}", if ovp { ch.ovp } else { ch.ocp }))
+                    }
+                    (["VAL"], false) => {
+                        let (idx, value) = self.ch_value_params(cmd)?;
+                        let (max_voltage, max_current) = RATINGS[idx];
+                        let max: f32 = if ovp { max_voltage } else { max_current };
+                        let value: f32 = parse_value(value, max * RANGE_FACTOR)?;
+                        let ch: &mut Channel = &mut self.channels[idx];
+                        *(if ovp { &mut ch.ovp } else { &mut ch.ocp }) = value;
+                        None
+                    }
+                    ([], true) => {
+                        let ch: &Channel = &self.channels[self.ch_param(cmd, 0)?];
+                        Some(on_off(if ovp { ch.ovp_on } else { ch.ocp_on }))
+                    }
+                    ([], false) => {
+                        let (idx, value) = self.ch_value_params(cmd)?;
+                        let on: bool = parse_on_off(value)?;
+                        let ch: &mut Channel = &mut self.channels[idx];
+                        *(if ovp { &mut ch.ovp_on } else { &mut ch.ocp_on }) = on;
+                        None
+                    }
+                    (["QUES"], true) => {
+                        let ch: &Channel = &self.channels[self.ch_param(cmd, 0)?];
+                        let tripped: bool = if ovp { ch.ovp_tripped } else { ch.ocp_tripped };
+                        Some(if tripped { "YES" } else { "NO" }.to_string())
+                    }
+                    (["CLE"], false) => {
+                        let idx: usize = self.ch_param(cmd, 0)?;
+                        let ch: &mut Channel = &mut self.channels[idx];
+                        *(if ovp {
+                            &mut ch.ovp_tripped
+                        } else {
+                            &mut ch.ocp_tripped
+                        }) = false;
+                        None
+                    }
+                    _ => return Err(UNDEFINED_HEADER),
+                }
+            }
+            (["MEAS", quantity @ ..], true) => {
+                let idx: usize = self.ch_param(cmd, 0)?;
+                let (voltage, current, _) = self.channels[idx].operating_point();
+                let power: f32 = voltage * current;
+                match quantity {
+                    ["ALL"] => Some(format!("{voltage:.3},{current:.3},{power:.3}")),
+                    [] | ["VOLT"] | ["VOLT", "DC"] => Some(format!("{voltage:.3}")),
+                    ["CURR"] | ["CURR", "DC"] => Some(format!("{current:.3}")),
+                    ["POWE"] | ["POWE", "DC"] => Some(format!("{power:.3}")),
+                    _ => return Err(UNDEFINED_HEADER),
+                }
+            }
+            (["SYST", "ERR"], true) => Some(match self.errors.pop_front() {
+                Some(Error(code, message)) => format!("{code},\"{message}\""),
+                None => "0,\"No error\"".to_string(),
+            }),
+            (["SYST", "VERS"], true) => Some("1999.0".to_string()),
+            (["SYST", "TRACKM"], true) => {
+                Some(if self.track_sync { "SYNC" } else { "INDE" }.to_string())
+            }
+            (["SYST", "TRACKM"], false) => {
+                self.track_sync = match cmd.params.first().map(String::as_str) {
+                    Some("SYNC") => true,
+                    Some("INDE" | "INDEPENDENT") => false,
+                    Some(_) => return Err(ILLEGAL_PARAMETER),
+                    None => return Err(MISSING_PARAMETER),
+                };
+                None
+            }
+            (["SYST", "REM" | "RWL" | "LOC"], false) => None,
+            _ => return Err(UNDEFINED_HEADER),
+        };
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NO_ERROR: &str = "0,\"No error\"";
+
+    #[test]
+    fn protection_range_per_channel() {
+        let mut instrument: Instrument = Instrument::new([f32::INFINITY; NUM_CH]);
+        instrument.execute(":OUTP:OVP:VAL CH1,33.0");
+        assert_eq!(instrument.execute(":SYST:ERR?").unwrap(), NO_ERROR);
+        instrument.execute(":OUTP:OVP:VAL CH3,6.0");
+        assert_eq!(
+            instrument.execute(":SYST:ERR?").unwrap(),
+            "-222,\"Data out of range\""
+        );
+        instrument.execute(":OUTP:OVP:VAL CH3,5.5");
+        assert_eq!(instrument.execute(":OUTP:OVP:VAL? CH3").unwrap(), "5.500");
+    }
+
+    #[test]
+    fn channel_suffix() {
+        let mut instrument: Instrument = Instrument::new([f32::INFINITY; NUM_CH]);
+        instrument.execute(":SOUR3:VOLT 1.8");
+        assert_eq!(instrument.execute(":SOUR3:VOLT?").unwrap(), "1.800");
+        instrument.execute(":SOUR4:VOLT 1.8");
+        assert_eq!(
+            instrument.execute(":SYST:ERR?").unwrap(),
+            "-113,\"Undefined header\""
+        );
+    }
+
+    #[test]
+    fn constant_current() {
+        let mut instrument: Instrument = Instrument::new([10.0, f32::INFINITY, f32::INFINITY]);
+        instrument.execute(":SOUR1:VOLT 5;:SOUR1:CURR 0.2;:OUTP CH1,ON");
+        assert_eq!(instrument.execute(":OUTP:MODE? CH1").unwrap(), "CC");
+        instrument.execute(":SOUR1:CURR 1");
+        assert_eq!(instrument.execute(":OUTP:MODE? CH1").unwrap(), "CV");
+    }
+}
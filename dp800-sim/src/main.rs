@@ -0,0 +1,182 @@
+use anyhow::Context;
+use fault::{Action, Faults};
+use instrument::Instrument;
+use std::{
+    io::{BufRead, BufReader, BufWriter, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+mod fault;
+mod instrument;
+mod scpi;
+
+/// Default load resistance in ohms.
+const DEFAULT_LOAD: f32 = 100.0;
+
+/// Command line arguments.
+struct Args {
+    /// Address to listen on.
+    listen: SocketAddr,
+    /// Load resistance of each channel in ohms.
+    loads: [f32; 3],
+    /// Fault injection script.
+    faults: Option<PathBuf>,
+}
+
+impl Args {
+    fn parse() -> anyhow::Result<Self> {
+        let mut args: Args = Args {
+            listen: SocketAddr::from(([127, 0, 0, 1], 5555)),
+            loads: [DEFAULT_LOAD; 3],
+            faults: None,
+        };
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--listen" => {
+                    let addr: String = iter.next().context("--listen requires an address")?;
+                    args.listen = addr
+                        .parse()
+                        .with_context(|| format!("Invalid address: {addr}"))?;
+                }
+                "--load" => {
+                    let load: String = iter.next().context("--load requires CH=OHMS")?;
+                    let (ch, ohms) = load
+                        .split_once('=')
+                        .with_context(|| format!("Invalid load: {load}"))?;
+                    let ch: usize = ch
+                        .trim_start_matches("CH")
+                        .parse()
+                        .ok()
+                        .filter(|ch| (1..=3).contains(ch))
+                        .with_context(|| format!("Invalid channel: {ch}"))?;
+                    args.loads[ch - 1] = match ohms {
+                        "open" => f32::INFINITY,
+                        _ => ohms
+                            .parse()
+                            .with_context(|| format!("Invalid resistance: {ohms}"))?,
+                    };
+                }
+                "--faults" => {
+                    args.faults = Some(iter.next().context("--faults requires a path")?.into());
+                }
+                "-h" | "--help" => {
+                    println!(
+                        "Usage: dp800-sim [--listen <addr>] [--load <ch>=<ohms>]... [--faults <path>]"
+                    );
+                    println!();
+                    println!("Options:");
+                    println!(
+                        "  --listen <addr>     Address to listen on [default: 127.0.0.1:5555]"
+                    );
+                    println!(
+                        "  --load <ch>=<ohms>  Load resistance of a channel, or \"open\" [default: {DEFAULT_LOAD}]"
+                    );
+                    println!("  --faults <path>     Fault injection script");
+                    std::process::exit(0);
+                }
+                _ => anyhow::bail!("Unknown argument: {arg}"),
+            }
+        }
+        Ok(args)
+    }
+}
+
+/// State shared by all connections.
+struct Simulator {
+    instrument: Mutex<Instrument>,
+    faults: Faults,
+    /// Number of messages received on all connections.
+    received: AtomicU64,
+}
+
+/// Line sent by [`Action::Garbage`].
+const GARBAGE: &str = "\u{7f}#?RIGOL";
+
+fn handle_client(stream: TcpStream, sim: &Simulator) -> std::io::Result<()> {
+    let mut reader: BufReader<TcpStream> = BufReader::new(stream.try_clone()?);
+    let mut writer: BufWriter<TcpStream> = BufWriter::new(stream);
+    let mut line: String = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let n: u64 = sim.received.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let mut instrument = sim
+            .instrument
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut response: Option<String> = instrument.execute(line.as_str());
+        let mut delay: Duration = Duration::ZERO;
+        for action in sim.faults.actions(n) {
+            eprintln!("Message {n}: {action:?}");
+            match action {
+                Action::Delay(d) => delay += *d,
+                Action::Drop => {
+                    writer.get_ref().shutdown(Shutdown::Both)?;
+                    return Ok(());
+                }
+                Action::Garbage => writeln!(writer, "{GARBAGE}")?,
+                Action::Ignore => response = None,
+                Action::Error(code, message) => instrument.push_error_code(*code, message.clone()),
+            }
+        }
+        drop(instrument);
+
+        // sleep without the lock so other clients are not delayed
+        std::thread::sleep(delay);
+        if let Some(response) = response {
+            writeln!(writer, "{response}")?;
+        }
+        writer.flush()?;
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let args: Args = Args::parse()?;
+
+    let listener: TcpListener = TcpListener::bind(args.listen)
+        .with_context(|| format!("Failed to listen on {}", args.listen))?;
+    eprintln!("Simulating DP832 on {}", args.listen);
+
+    let faults: Faults = match args.faults {
+        Some(path) => Faults::load(&path)?,
+        None => Faults::default(),
+    };
+    let mut instrument: Instrument = Instrument::new(args.loads);
+    instrument.set_nsel_guard(faults.nsel_guard);
+    let sim: Arc<Simulator> = Arc::new(Simulator {
+        instrument: Mutex::new(instrument),
+        faults,
+        received: AtomicU64::new(0),
+    });
+
+    for stream in listener.incoming() {
+        let stream: TcpStream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Failed to accept connection: {e}");
+                continue;
+            }
+        };
+        let peer: String = stream
+            .peer_addr()
+            .map_or_else(|_| "unknown".to_string(), |addr| addr.to_string());
+        eprintln!("Connection from {peer}");
+        let sim: Arc<Simulator> = Arc::clone(&sim);
+        std::thread::spawn(move || match handle_client(stream, &sim) {
+            Ok(()) => eprintln!("Connection from {peer} closed"),
+            Err(e) => eprintln!("Connection from {peer} failed: {e}"),
+        });
+    }
+
+    Ok(())
+}
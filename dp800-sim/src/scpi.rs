@@ -0,0 +1,187 @@
+/// Long form to short form mnemonics.
+const MNEMONICS: &[(&str, &str)] = &[
+    ("AMPLITUDE", "AMPL"),
+    ("CLEAR", "CLE"),
+    ("CURRENT", "CURR"),
+    ("ERROR", "ERR"),
+    ("IMMEDIATE", "IMM"),
+    ("INSTRUMENT", "INST"),
+    ("LEVEL", "LEV"),
+    ("LOCAL", "LOC"),
+    ("MEASURE", "MEAS"),
+    ("NSELECT", "NSEL"),
+    ("OUTPUT", "OUTP"),
+    ("POWER", "POWE"),
+    ("QUESTION", "QUES"),
+    ("REMOTE", "REM"),
+    ("RWLOCK", "RWL"),
+    ("SOURCE", "SOUR"),
+    ("STATE", "STAT"),
+    ("SYSTEM", "SYST"),
+    ("TRACK", "TRAC"),
+    ("TRACKMODE", "TRACKM"),
+    ("VALUE", "VAL"),
+    ("VERSION", "VERS"),
+    ("VOLTAGE", "VOLT"),
+];
+
+/// Optional nodes that are dropped from headers.
+const OPTIONAL: &[&str] = &["STAT", "LEV", "IMM", "AMPL"];
+
+/// Parsed SCPI command.
+#[derive(Debug, PartialEq)]
+pub struct Command {
+    /// Header nodes in short form, with optional nodes removed.
+    pub path: Vec<String>,
+    /// Numeric suffix of the first header node, for example `2` in `:SOUR2`.
+    pub suffix: Option<u8>,
+    /// `true` if the command is a query.
+    pub query: bool,
+    /// Comma separated parameters.
+    pub params: Vec<String>,
+}
+
+impl Command {
+    /// Parse a single command, returns `None` for an empty command.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s: &str = s.trim();
+        if s.is_empty() {
+            return None;
+        }
+
+        let (header, params) = match s.split_once(char::is_whitespace) {
+            Some((header, params)) => (header, params.trim()),
+            None => (s, ""),
+        };
+
+        let (header, query) = match header.strip_suffix('?') {
+            Some(header) => (header, true),
+            None => (header, false),
+        };
+
+        let mut suffix: Option<u8> = None;
+        let mut path: Vec<String> = Vec::new();
+        for (idx, node) in header.trim_start_matches(':').split(':').enumerate() {
+            let node: String = node.to_ascii_uppercase();
+            let name: &str = node.trim_end_matches(|c: char| c.is_ascii_digit());
+            if idx == 0 && name.len() != node.len() && !name.starts_with('*') {
+                suffix = node[name.len()..].parse().ok();
+            }
+            let short: &str = MNEMONICS
+                .iter()
+                .find(|(long, _)| *long == name)
+                .map_or(name, |(_, short)| short);
+            path.push(short.to_string());
+        }
+
+        // optional nodes are only dropped after the root node
+        let root: String = path.remove(0);
+        path.retain(|node| !OPTIONAL.contains(&node.as_str()));
+        path.insert(0, root);
+
+        let params: Vec<String> = if params.is_empty() {
+            Vec::new()
+        } else {
+            params
+                .split(',')
+                .map(|p| p.trim().trim_matches('"').to_ascii_uppercase())
+                .collect()
+        };
+
+        Some(Self {
+            path,
+            suffix,
+            query,
+            params,
+        })
+    }
+}
+
+/// Split a message into commands at semicolons outside of quotes.
+pub fn split_message(msg: &str) -> Vec<&str> {
+    let mut commands: Vec<&str> = Vec::new();
+    let mut quoted: bool = false;
+    let mut start: usize = 0;
+    for (idx, c) in msg.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                commands.push(&msg[start..idx]);
+                start = idx + 1;
+            }
+            _ => (),
+        }
+    }
+    commands.push(&msg[start..]);
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_long_form() {
+        assert_eq!(
+            Command::parse(":SOURce2:VOLTage:LEVel:IMMediate:AMPLitude 3.3"),
+            Some(Command {
+                path: vec!["SOUR".to_string(), "VOLT".to_string()],
+                suffix: Some(2),
+                query: false,
+                params: vec!["3.3".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_query_params() {
+        assert_eq!(
+            Command::parse("meas:all? ch1"),
+            Some(Command {
+                path: vec!["MEAS".to_string(), "ALL".to_string()],
+                suffix: None,
+                query: true,
+                params: vec!["CH1".to_string()],
+            })
+        );
+        assert_eq!(
+            Command::parse(":OUTP:OVP:VAL CH3 , 5.5").unwrap().params,
+            ["CH3", "5.5"]
+        );
+    }
+
+    #[test]
+    fn parse_common() {
+        let cmd: Command = Command::parse("*IDN?").unwrap();
+        assert_eq!(cmd.path, ["*IDN"]);
+        assert_eq!(cmd.suffix, None);
+        assert!(cmd.query);
+        assert!(cmd.params.is_empty());
+    }
+
+    #[test]
+    fn parse_optional_root() {
+        let cmd: Command = Command::parse(":OUTP:STAT CH1,ON").unwrap();
+        assert_eq!(cmd.path, ["OUTP"]);
+        assert_eq!(cmd.params, ["CH1", "ON"]);
+    }
+
+    #[test]
+    fn parse_empty() {
+        assert_eq!(Command::parse(""), None);
+        assert_eq!(Command::parse("  "), None);
+    }
+
+    #[test]
+    fn split_quoted() {
+        assert_eq!(
+            split_message(":SOUR1:VOLT?;*IDN?"),
+            [":SOUR1:VOLT?", "*IDN?"]
+        );
+        assert_eq!(
+            split_message(":SYST:TEXT \"a;b\";*OPC?"),
+            [":SYST:TEXT \"a;b\"", "*OPC?"]
+        );
+        assert_eq!(split_message(""), [""]);
+    }
+}
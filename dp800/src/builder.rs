@@ -0,0 +1,121 @@
+use crate::{Dp800, TcpTransport, Transport};
+use std::{
+    io,
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+/// DP800 power supply connection builder.
+#[derive(Debug, Clone)]
+pub struct Dp800Builder {
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+    command_delay: Duration,
+}
+
+impl Default for Dp800Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dp800Builder {
+    /// Create a new builder with the default settings.
+    ///
+    /// * No connect timeout
+    /// * 1 second read timeout
+    /// * No write timeout
+    /// * No command delay
+    pub fn new() -> Self {
+        Self {
+            connect_timeout: None,
+            read_timeout: Some(Duration::from_secs(1)),
+            write_timeout: None,
+            command_delay: Duration::ZERO,
+        }
+    }
+
+    /// Set the connect timeout, `None` to wait indefinitely.
+    #[must_use]
+    pub fn connect_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.connect_timeout = timeout.into();
+        self
+    }
+
+    /// Set the read timeout, `None` to wait indefinitely.
+    #[must_use]
+    pub fn read_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.read_timeout = timeout.into();
+        self
+    }
+
+    /// Set the write timeout, `None` to wait indefinitely.
+    #[must_use]
+    pub fn write_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.write_timeout = timeout.into();
+        self
+    }
+
+    /// Set the minimum delay between a command and the next message sent to
+    /// the instrument.
+    ///
+    /// Sending commands too quickly can cause the instrument to report
+    /// invalid commands.
+    /// Queries are not delayed after their response is received.
+    #[must_use]
+    pub fn command_delay(mut self, delay: Duration) -> Self {
+        self.command_delay = delay;
+        self
+    }
+
+    fn connect_stream<A: ToSocketAddrs>(&self, addr: A) -> io::Result<TcpStream> {
+        let Some(timeout) = self.connect_timeout else {
+            return TcpStream::connect(addr);
+        };
+
+        let mut last_err: Option<io::Error> = None;
+        for addr in addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Could not resolve to any addresses",
+            )
+        }))
+    }
+
+    /// Connect to the power supply.
+    pub fn connect<A: ToSocketAddrs>(&self, addr: A) -> io::Result<Dp800> {
+        Ok(self.build(self.connect_tcp(addr)?))
+    }
+
+    /// Connect a TCP transport with the configured timeouts.
+    ///
+    /// This is useful to wrap the transport, for example with a
+    /// [`Recorder`](crate::Recorder), before passing it to
+    /// [`Dp800Builder::build`].
+    pub fn connect_tcp<A: ToSocketAddrs>(&self, addr: A) -> io::Result<TcpTransport> {
+        let stream: TcpStream = self.connect_stream(addr)?;
+        stream.set_read_timeout(self.read_timeout)?;
+        stream.set_write_timeout(self.write_timeout)?;
+        TcpTransport::new(stream)
+    }
+
+    /// Create a power supply using an existing transport.
+    ///
+    /// Timeouts are not applied to the transport.
+    pub fn build<T: Transport + 'static>(&self, transport: T) -> Dp800 {
+        Dp800 {
+            transport: Box::new(transport),
+            identity: None,
+            desync: false,
+            command_delay: self.command_delay,
+            last_cmd: None,
+        }
+    }
+}
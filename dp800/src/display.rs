@@ -0,0 +1,89 @@
+use crate::{Dp800, State, parse_error};
+use std::{io, str::FromStr};
+
+/// Display mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DisplayMode {
+    /// Numeric display of all channels.
+    Normal,
+    /// Waveform display.
+    Wave,
+    /// Dial display.
+    Dial,
+    /// Classic display.
+    Classic,
+}
+
+impl FromStr for DisplayMode {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NORM" | "NORMAL" => Ok(Self::Normal),
+            "WAVE" => Ok(Self::Wave),
+            "DIAL" => Ok(Self::Dial),
+            "CLAS" | "CLASSIC" => Ok(Self::Classic),
+            _ => Err(parse_error()),
+        }
+    }
+}
+
+impl std::fmt::Display for DisplayMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Normal => write!(f, "NORM"),
+            Self::Wave => write!(f, "WAVE"),
+            Self::Dial => write!(f, "DIAL"),
+            Self::Classic => write!(f, "CLAS"),
+        }
+    }
+}
+
+impl Dp800 {
+    /// Returns `true` if the display is on.
+    pub fn display_on(&mut self) -> io::Result<bool> {
+        self.q_bool(":DISP?\n")
+    }
+
+    /// Turn the display on or off.
+    pub fn set_display_on(&mut self, on: bool) -> io::Result<()> {
+        let state: State = on.into();
+        self.cmd(format!(":DISP {state}\n").as_str())
+    }
+
+    /// Display mode.
+    pub fn display_mode(&mut self) -> io::Result<DisplayMode> {
+        self.q_parse(":DISP:MODE?\n")
+    }
+
+    /// Set the display mode.
+    pub fn set_display_mode(&mut self, mode: DisplayMode) -> io::Result<()> {
+        self.cmd(format!(":DISP:MODE {mode}\n").as_str())
+    }
+
+    /// Text shown on the display.
+    pub fn display_text(&mut self) -> io::Result<String> {
+        let text: String = self.q(":DISP:TEXT?\n")?;
+        Ok(text.trim_matches('"').to_string())
+    }
+
+    /// Show text on the display.
+    ///
+    /// The text must not contain double quotes or newlines.
+    pub fn set_display_text(&mut self, text: &str) -> io::Result<()> {
+        if text.contains(['"', '\n', '\r']) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Display text must not contain double quotes or newlines",
+            ));
+        }
+        self.cmd(format!(":DISP:TEXT \"{text}\"\n").as_str())
+    }
+
+    /// Clear text shown with [`Dp800::set_display_text`].
+    pub fn clear_display_text(&mut self) -> io::Result<()> {
+        self.cmd(":DISP:TEXT:CLE\n")
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{Dp800, State, parse};
+use std::{io, net::Ipv4Addr};
+
+/// LAN interface configuration.
+///
+/// Returned by [`Dp800::lan_config`], and applied with
+/// [`Dp800::set_lan_config`].
+///
+/// When more than one IP configuration mode is enabled the instrument tries
+/// DHCP first, then AutoIP, then the manual IP settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LanConfig {
+    /// DHCP configuration mode enabled.
+    pub dhcp: bool,
+    /// AutoIP configuration mode enabled.
+    pub auto_ip: bool,
+    /// Manual IP configuration mode enabled.
+    pub manual_ip: bool,
+    /// IP address.
+    pub ip: Ipv4Addr,
+    /// Subnet mask.
+    pub mask: Ipv4Addr,
+    /// Default gateway.
+    pub gateway: Ipv4Addr,
+    /// DNS server.
+    pub dns: Ipv4Addr,
+}
+
+impl Dp800 {
+    fn q_ip(&mut self, query: &str) -> io::Result<Ipv4Addr> {
+        let s: String = self.q(query)?;
+        parse(s.trim_matches('"'))
+    }
+
+    /// Returns `true` if the DHCP configuration mode is enabled.
+    pub fn lan_dhcp_on(&mut self) -> io::Result<bool> {
+        self.q_bool(":SYST:COMM:LAN:DHCP?\n")
+    }
+
+    /// Enable or disable the DHCP configuration mode.
+    pub fn set_lan_dhcp_on(&mut self, on: bool) -> io::Result<()> {
+        let state: State = on.into();
+        self.cmd(format!(":SYST:COMM:LAN:DHCP {state}\n").as_str())
+    }
+
+    /// Returns `true` if the AutoIP configuration mode is enabled.
+    pub fn lan_auto_ip_on(&mut self) -> io::Result<bool> {
+        self.q_bool(":SYST:COMM:LAN:AUTO?\n")
+    }
+
+    /// Enable or disable the AutoIP configuration mode.
+    pub fn set_lan_auto_ip_on(&mut self, on: bool) -> io::Result<()> {
+        let state: State = on.into();
+        self.cmd(format!(":SYST:COMM:LAN:AUTO {state}\n").as_str())
+    }
+
+    /// Returns `true` if the manual IP configuration mode is enabled.
+    pub fn lan_manual_ip_on(&mut self) -> io::Result<bool> {
+        self.q_bool(":SYST:COMM:LAN:MAN?\n")
+    }
+
+    /// Enable or disable the manual IP configuration mode.
+    pub fn set_lan_manual_ip_on(&mut self, on: bool) -> io::Result<()> {
+        let state: State = on.into();
+        self.cmd(format!(":SYST:COMM:LAN:MAN {state}\n").as_str())
+    }
+
+    /// IP address.
+    pub fn lan_ip(&mut self) -> io::Result<Ipv4Addr> {
+        self.q_ip(":SYST:COMM:LAN:IPAD?\n")
+    }
+
+    /// Set the IP address used in manual IP configuration mode.
+    pub fn set_lan_ip(&mut self, ip: Ipv4Addr) -> io::Result<()> {
+        self.cmd(format!(":SYST:COMM:LAN:IPAD {ip}\n").as_str())
+    }
+
+    /// Subnet mask.
+    pub fn lan_mask(&mut self) -> io::Result<Ipv4Addr> {
+        self.q_ip(":SYST:COMM:LAN:SMAS?\n")
+    }
+
+    /// Set the subnet mask used in manual IP configuration mode.
+    pub fn set_lan_mask(&mut self, mask: Ipv4Addr) -> io::Result<()> {
+        self.cmd(format!(":SYST:COMM:LAN:SMAS {mask}\n").as_str())
+    }
+
+    /// Default gateway.
+    pub fn lan_gateway(&mut self) -> io::Result<Ipv4Addr> {
+        self.q_ip(":SYST:COMM:LAN:GATE?\n")
+    }
+
+    /// Set the default gateway used in manual IP configuration mode.
+    pub fn set_lan_gateway(&mut self, gateway: Ipv4Addr) -> io::Result<()> {
+        self.cmd(format!(":SYST:COMM:LAN:GATE {gateway}\n").as_str())
+    }
+
+    /// DNS server.
+    pub fn lan_dns(&mut self) -> io::Result<Ipv4Addr> {
+        self.q_ip(":SYST:COMM:LAN:DNS?\n")
+    }
+
+    /// Set the DNS server used in manual IP configuration mode.
+    pub fn set_lan_dns(&mut self, dns: Ipv4Addr) -> io::Result<()> {
+        self.cmd(format!(":SYST:COMM:LAN:DNS {dns}\n").as_str())
+    }
+
+    /// MAC address of the LAN interface.
+    pub fn lan_mac(&mut self) -> io::Result<String> {
+        self.q(":SYST:COMM:LAN:MAC?\n")
+    }
+
+    /// Apply the LAN settings.
+    ///
+    /// Changes to the LAN settings do not take effect until this is called.
+    /// If the connection to the instrument is over LAN it will likely be
+    /// dropped.
+    pub fn lan_apply(&mut self) -> io::Result<()> {
+        self.cmd(":SYST:COMM:LAN:APPL\n")
+    }
+
+    /// Read the LAN configuration.
+    pub fn lan_config(&mut self) -> io::Result<LanConfig> {
+        Ok(LanConfig {
+            dhcp: self.lan_dhcp_on()?,
+            auto_ip: self.lan_auto_ip_on()?,
+            manual_ip: self.lan_manual_ip_on()?,
+            ip: self.lan_ip()?,
+            mask: self.lan_mask()?,
+            gateway: self.lan_gateway()?,
+            dns: self.lan_dns()?,
+        })
+    }
+
+    /// Write the LAN configuration.
+    ///
+    /// This does not apply the settings, call [`Dp800::lan_apply`] afterwards.
+    pub fn set_lan_config(&mut self, config: &LanConfig) -> io::Result<()> {
+        self.set_lan_dhcp_on(config.dhcp)?;
+        self.set_lan_auto_ip_on(config.auto_ip)?;
+        self.set_lan_manual_ip_on(config.manual_ip)?;
+        self.set_lan_ip(config.ip)?;
+        self.set_lan_mask(config.mask)?;
+        self.set_lan_gateway(config.gateway)?;
+        self.set_lan_dns(config.dns)
+    }
+}
@@ -3,13 +3,68 @@
 //! See the [DP800 Series Programming Guide] for more information.
 //!
 //! [DP800 Series Programming Guide]: https://www.batronix.com/pdf/Rigol/ProgrammingGuide/DP800_ProgrammingGuide_EN.pdf
+//!
+//! # Features
+//!
+//! * `serde`: Derive `Serialize` and `Deserialize` for [`Measurement`],
+//!   [`Identify`], [`InstrumentState`], and the other data types.
+//!   Field names are part of the stable API, and quantities are always in
+//!   volts, amps, and watts.
+//!   Enum variants are serialized in `snake_case`, except for [`Model`]
+//!   which uses the model name, for example `"DP832"`.
+//! * `profile`: Device under test configuration profiles stored as TOML,
+//!   see `Profile`.
+//!
+//! # Logging
+//!
+//! SCPI traffic is logged with the [`log`] crate.
+//! Every message sent and response received is logged at the trace level,
+//! responses include the round-trip latency.
 
 use std::{
-    io::{self, BufRead, BufReader, BufWriter, Write},
-    net::{TcpStream, ToSocketAddrs},
+    borrow::Cow,
+    io,
+    net::ToSocketAddrs,
     str::FromStr,
+    time::{Duration, Instant},
 };
 
+mod builder;
+mod display;
+mod lan;
+mod model;
+mod poller;
+#[cfg(feature = "profile")]
+mod profile;
+mod ramp;
+mod reconnect;
+mod sample;
+mod sequence;
+mod shared;
+mod state;
+mod system;
+mod transport;
+mod units;
+mod waveform;
+
+pub use builder::Dp800Builder;
+pub use display::DisplayMode;
+pub use lan::LanConfig;
+pub use model::Model;
+pub use poller::{Poller, Subscription};
+#[cfg(feature = "profile")]
+pub use profile::{ChannelProfile, Mismatch, Profile};
+pub use ramp::{Ramp, RampOutcome};
+pub use reconnect::{Backoff, ConnectionEvent, Reconnecting};
+pub use sample::Sample;
+pub use sequence::{Precondition, Sequence, Step};
+pub use shared::SharedDp800;
+pub use state::{ChannelState, InstrumentState, Status};
+pub use system::{Language, PowerOnState, SystemError, SystemSettings, TrackMode};
+pub use transport::{Recorder, Replay, TcpTransport, Transport};
+pub use units::{Amps, Volts, Watts};
+pub use waveform::{PlaybackReport, Point, Timing, Waveform};
+
 fn parse_error() -> io::Error {
     io::Error::other("Parse error")
 }
@@ -25,10 +80,40 @@ where
     }
 }
 
+/// Append a newline terminator to a message if not present.
+fn terminate(msg: &str) -> Cow<'_, str> {
+    if msg.ends_with('\n') {
+        Cow::Borrowed(msg)
+    } else {
+        Cow::Owned(format!("{msg}\n"))
+    }
+}
+
+/// Split a response to multiple queries on semicolons outside of quoted
+/// strings.
+fn split_responses(response: &str) -> Vec<String> {
+    let mut responses: Vec<String> = Vec::new();
+    let mut quoted: bool = false;
+    let mut start: usize = 0;
+    for (idx, c) in response.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                responses.push(response[start..idx].to_string());
+                start = idx + 1;
+            }
+            _ => (),
+        }
+    }
+    responses.push(response[start..].to_string());
+    responses
+}
+
 /// Power supply identification strings.
 ///
 /// Returned by [`Dp800::measure`].
-#[derive(Debug)]
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Measurement {
     /// Voltage in volts.
     pub voltage: f32,
@@ -55,7 +140,8 @@ impl FromStr for Measurement {
 /// Power supply identification strings.
 ///
 /// Returned by [`Dp800::identify`].
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Identify {
     /// Manufacturer name
     pub manufacturer: String,
@@ -126,6 +212,34 @@ impl std::fmt::Display for State {
     }
 }
 
+/// Output regulation mode.
+///
+/// Returned by [`Dp800::output_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum OutputMode {
+    /// Constant voltage.
+    ConstantVoltage,
+    /// Constant current.
+    ConstantCurrent,
+    /// Unregulated.
+    Unregulated,
+}
+
+impl FromStr for OutputMode {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "CV" => Ok(Self::ConstantVoltage),
+            "CC" => Ok(Self::ConstantCurrent),
+            "UR" => Ok(Self::Unregulated),
+            _ => Err(parse_error()),
+        }
+    }
+}
+
 /// DP800 power supply.
 ///
 /// # Channel Indexing
@@ -133,35 +247,138 @@ impl std::fmt::Display for State {
 /// * Channels are 1-indexed
 /// * Out-of-bounds values for channels will return the value for the
 ///   currently selected channel
+///
+/// # Response Synchronization
+///
+/// A response that arrives after the read timeout would otherwise be read as
+/// the response to the next query.
+/// To prevent this stale input is discarded before every query, and after a
+/// read timeout or unparsable response the next query is preceded by
+/// [`Dp800::resync`].
 pub struct Dp800 {
-    reader: BufReader<TcpStream>,
-    writer: BufWriter<TcpStream>,
+    transport: Box<dyn Transport>,
+    identity: Option<Identify>,
+    desync: bool,
+    command_delay: Duration,
+    last_cmd: Option<Instant>,
 }
 
 impl Dp800 {
+    /// Connect to the power supply with the default settings.
+    ///
+    /// Use [`Dp800Builder`] to configure timeouts and command pacing.
     pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
-        let stream: TcpStream = std::net::TcpStream::connect(addr)?;
-        stream.set_read_timeout(Some(std::time::Duration::from_secs(1)))?;
-        Ok(Self {
-            reader: BufReader::new(stream.try_clone()?),
-            writer: BufWriter::new(stream),
-        })
+        Dp800Builder::new().connect(addr)
+    }
+
+    /// Write a message, waiting for the command delay to elapse if required.
+    fn write(&mut self, msg: &str) -> io::Result<()> {
+        if let Some(last_cmd) = self.last_cmd.take() {
+            let elapsed: Duration = last_cmd.elapsed();
+            if elapsed < self.command_delay {
+                std::thread::sleep(self.command_delay - elapsed);
+            }
+        }
+        log::trace!("-> {}", msg.trim_end());
+        self.transport.write(msg)
+    }
+
+    /// Discard any input received from the instrument.
+    fn drain(&mut self) -> io::Result<()> {
+        let discarded: usize = self.transport.drain()?;
+        if discarded != 0 {
+            log::debug!("Discarded {discarded} bytes of stale input");
+        }
+        Ok(())
+    }
+
+    /// Resynchronize responses with queries.
+    ///
+    /// Stale input is discarded, then a tagged query is sent and responses
+    /// are discarded until the tagged response is received.
+    ///
+    /// This is called automatically after a read timeout or an unparsable
+    /// response.
+    pub fn resync(&mut self) -> io::Result<()> {
+        const MAX_STALE_RESPONSES: usize = 16;
+
+        log::debug!("Resynchronizing responses");
+        self.desync = true;
+        self.drain()?;
+        self.write("*OPC?;*IDN?\n")?;
+
+        for _ in 0..MAX_STALE_RESPONSES {
+            let response: String = self.transport.read_line()?;
+            log::trace!("<- {response}");
+            if let Some(("1", idn)) = response.split_once(';')
+                && idn.parse::<Identify>().is_ok()
+            {
+                self.desync = false;
+                return Ok(());
+            }
+        }
+
+        Err(io::Error::other("Failed to resynchronize responses"))
+    }
+
+    /// Clear the status registers and error queue, and resynchronize
+    /// responses with queries.
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.drain()?;
+        self.cmd("*CLS\n")?;
+        self.resync()
     }
 
     fn cmd(&mut self, cmd: &str) -> io::Result<()> {
-        self.writer.write_all(cmd.as_bytes())?;
-        self.writer.flush()
+        self.write(cmd)?;
+        self.last_cmd = Some(Instant::now());
+        Ok(())
     }
 
     fn q(&mut self, query: &str) -> io::Result<String> {
-        let mut buf: String = String::with_capacity(64);
-        {
-            self.writer.write_all(query.as_bytes())?;
-            self.writer.flush()?;
-            self.reader.read_line(&mut buf)?;
+        if self.desync {
+            self.resync()?;
+        } else {
+            self.drain()?;
+        }
+
+        self.write(query)?;
+        let start: Instant = Instant::now();
+        match self.transport.read_line() {
+            Ok(response) => {
+                log::trace!("<- {response} ({:?})", start.elapsed());
+                Ok(response)
+            }
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Err(e),
+            Err(e) => {
+                log::debug!("No response after {:?}: {e}", start.elapsed());
+                self.desync = true;
+                Err(e)
+            }
         }
-        buf.pop();
-        Ok(buf)
+    }
+
+    /// Send multiple queries in a single message and return the responses.
+    ///
+    /// Queries are joined with semicolons, and must not be terminated with a
+    /// newline, for example `[":SOUR1:VOLT?", ":SOUR1:CURR?"]`.
+    /// This takes a single round-trip regardless of the number of queries.
+    pub fn query_batch<S: AsRef<str>>(&mut self, queries: &[S]) -> io::Result<Vec<String>> {
+        let mut msg: String = String::with_capacity(queries.len() * 16);
+        for (idx, query) in queries.iter().enumerate() {
+            if idx != 0 {
+                msg.push(';');
+            }
+            msg.push_str(query.as_ref());
+        }
+        msg.push('\n');
+        let response: String = self.q(msg.as_str())?;
+        let responses: Vec<String> = split_responses(response.as_str());
+        if responses.len() != queries.len() {
+            self.desync = true;
+            return Err(parse_error());
+        }
+        Ok(responses)
     }
 
     fn q_parse<F>(&mut self, query: &str) -> io::Result<F>
@@ -169,7 +386,9 @@ impl Dp800 {
         F: FromStr,
     {
         let s: String = self.q(query)?;
-        parse::<F>(s.as_str())
+        let result: io::Result<F> = parse::<F>(s.as_str());
+        self.desync |= result.is_err();
+        result
     }
 
     fn q_bool(&mut self, query: &str) -> io::Result<bool> {
@@ -177,11 +396,74 @@ impl Dp800 {
         Ok(state.into())
     }
 
+    /// Send a raw SCPI command.
+    ///
+    /// A newline terminator is appended if not present.
+    /// See the [DP800 Series Programming Guide] for a list of commands.
+    ///
+    /// [DP800 Series Programming Guide]: https://www.batronix.com/pdf/Rigol/ProgrammingGuide/DP800_ProgrammingGuide_EN.pdf
+    pub fn write_raw(&mut self, cmd: &str) -> io::Result<()> {
+        self.cmd(terminate(cmd).as_ref())
+    }
+
+    /// Send a raw SCPI query and return the response without the newline
+    /// terminator.
+    ///
+    /// A newline terminator is appended if not present.
+    pub fn query_raw(&mut self, query: &str) -> io::Result<String> {
+        self.q(terminate(query).as_ref())
+    }
+
+    /// Send a raw SCPI query and parse the response.
+    ///
+    /// A newline terminator is appended if not present.
+    pub fn query_parse<F: FromStr>(&mut self, query: &str) -> io::Result<F> {
+        self.q_parse(terminate(query).as_ref())
+    }
+
     /// Idenitfy the power supply.
     pub fn identify(&mut self) -> io::Result<Identify> {
         self.q_parse("*IDN?\n")
     }
 
+    /// Identification read on the first call and cached for the lifetime of
+    /// the connection.
+    fn cached_identify(&mut self) -> io::Result<&Identify> {
+        let identity: Identify = match self.identity.take() {
+            Some(identity) => identity,
+            None => self.identify()?,
+        };
+        Ok(self.identity.insert(identity))
+    }
+
+    /// Instrument model.
+    ///
+    /// The model is read with [`Dp800::identify`] on the first call and
+    /// cached for the lifetime of the connection.
+    ///
+    /// Returns an [`io::ErrorKind::Unsupported`] error if the model is not
+    /// a [`Model`].
+    /// Methods that depend on the channel layout return this error rather
+    /// than guess the number of channels.
+    /// Setters of unsupported models round to 3 decimal places.
+    pub fn model(&mut self) -> io::Result<Model> {
+        let name: &str = self.cached_identify()?.model.as_str();
+        name.parse().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("Unsupported model: {name}"),
+            )
+        })
+    }
+
+    /// Instrument serial number.
+    ///
+    /// The serial number is read with [`Dp800::identify`] on the first call
+    /// and cached for the lifetime of the connection.
+    pub fn serial_number(&mut self) -> io::Result<String> {
+        Ok(self.cached_identify()?.sn.clone())
+    }
+
     /// Output state.
     pub fn output_state(&mut self, ch: u8) -> io::Result<bool> {
         self.q_bool(format!(":OUTP? CH{ch}\n").as_str())
@@ -193,6 +475,25 @@ impl Dp800 {
         self.cmd(format!(":OUTP CH{ch},{state}\n").as_str())
     }
 
+    /// Output regulation mode.
+    pub fn output_mode(&mut self, ch: u8) -> io::Result<OutputMode> {
+        self.q_parse(format!(":OUTP:MODE? CH{ch}\n").as_str())
+    }
+
+    /// Returns `true` if tracking is enabled.
+    ///
+    /// Tracking is only available on some channels, see
+    /// [`Model::has_tracking`].
+    pub fn tracking(&mut self, ch: u8) -> io::Result<bool> {
+        self.q_bool(format!(":OUTP:TRAC? CH{ch}\n").as_str())
+    }
+
+    /// Enable or disable tracking.
+    pub fn set_tracking(&mut self, ch: u8, on: bool) -> io::Result<()> {
+        let state: State = on.into();
+        self.cmd(format!(":OUTP:TRAC CH{ch},{state}\n").as_str())
+    }
+
     /// Currently selected channel.
     pub fn ch(&mut self) -> io::Result<u8> {
         self.q_parse(":INST:NSEL?\n")
@@ -209,8 +510,35 @@ impl Dp800 {
     }
 
     /// Set the current setpoint in Amps.
-    pub fn set_current(&mut self, ch: u8, amps: f32) -> io::Result<()> {
-        self.cmd(format!(":SOUR{ch}:CURR {amps:.3}\n").as_str())
+    ///
+    /// The current is rounded to the resolution of the model, the current
+    /// applied is returned.
+    pub fn set_current(&mut self, ch: u8, amps: impl Into<Amps>) -> io::Result<Amps> {
+        let amps: String = self.format_current(amps.into())?;
+        self.cmd(format!(":SOUR{ch}:CURR {amps}\n").as_str())?;
+        parse(amps.as_str()).map(Amps)
+    }
+
+    /// Setting resolution of the model in decimal places, 3 decimal places
+    /// if the model is not supported.
+    fn decimals(&mut self, decimals: fn(&Model) -> usize) -> io::Result<usize> {
+        match self.model() {
+            Ok(model) => Ok(decimals(&model)),
+            Err(e) if e.kind() == io::ErrorKind::Unsupported => Ok(3),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Format a voltage with the resolution of the model.
+    fn format_voltage(&mut self, Volts(volts): Volts) -> io::Result<String> {
+        let decimals: usize = self.decimals(Model::voltage_decimals)?;
+        Ok(format!("{volts:.decimals$}"))
+    }
+
+    /// Format a current with the resolution of the model.
+    fn format_current(&mut self, Amps(amps): Amps) -> io::Result<String> {
+        let decimals: usize = self.decimals(Model::current_decimals)?;
+        Ok(format!("{amps:.decimals$}"))
     }
 
     /// Setpoint voltage in Volts.
@@ -219,8 +547,13 @@ impl Dp800 {
     }
 
     /// Set the voltage setpoint in Volts.
-    pub fn set_voltage(&mut self, ch: u8, volts: f32) -> io::Result<()> {
-        self.cmd(format!(":SOUR{ch}:VOLT {volts:.3}\n").as_str())
+    ///
+    /// The voltage is rounded to the resolution of the model, the voltage
+    /// applied is returned.
+    pub fn set_voltage(&mut self, ch: u8, volts: impl Into<Volts>) -> io::Result<Volts> {
+        let volts: String = self.format_voltage(volts.into())?;
+        self.cmd(format!(":SOUR{ch}:VOLT {volts}\n").as_str())?;
+        parse(volts.as_str()).map(Volts)
     }
 
     /// Get a measurement of voltage, current, and power.
@@ -228,14 +561,69 @@ impl Dp800 {
         self.q_parse(format!(":MEAS:ALL? CH{ch}\n").as_str())
     }
 
+    /// Measure the voltage in Volts.
+    pub fn measure_voltage(&mut self, ch: u8) -> io::Result<f32> {
+        self.q_parse(format!(":MEAS:VOLT? CH{ch}\n").as_str())
+    }
+
+    /// Measure the current in Amps.
+    pub fn measure_current(&mut self, ch: u8) -> io::Result<f32> {
+        self.q_parse(format!(":MEAS:CURR? CH{ch}\n").as_str())
+    }
+
+    /// Measure the power in Watts.
+    pub fn measure_power(&mut self, ch: u8) -> io::Result<f32> {
+        self.q_parse(format!(":MEAS:POWE? CH{ch}\n").as_str())
+    }
+
+    /// Get a measurement of voltage, current, and power for every channel.
+    ///
+    /// All channels are measured in a single round-trip.
+    ///
+    /// `N` must match [`Model::num_channels`] for the connected instrument,
+    /// for example 3 for the DP832.
+    pub fn measure_all_channels<const N: usize>(&mut self) -> io::Result<[Measurement; N]> {
+        let num_channels: u8 = self.model()?.num_channels();
+        if usize::from(num_channels) != N {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Instrument has {num_channels} channels, expected {N}"),
+            ));
+        }
+
+        let channels: Vec<u8> = (1..=num_channels).collect();
+        let measurements: Vec<Measurement> = self.measure_channels(&channels)?;
+        measurements.try_into().map_err(|_| parse_error())
+    }
+
+    /// Get a measurement of voltage, current, and power for each of the
+    /// given channels.
+    ///
+    /// All channels are measured in a single round-trip.
+    pub fn measure_channels(&mut self, channels: &[u8]) -> io::Result<Vec<Measurement>> {
+        let queries: Vec<String> = channels
+            .iter()
+            .map(|ch| format!(":MEAS:ALL? CH{ch}"))
+            .collect();
+        self.query_batch(&queries)?
+            .iter()
+            .map(|s| parse(s))
+            .collect()
+    }
+
     /// Over current protection value in Amps.
     pub fn ocp(&mut self, ch: u8) -> io::Result<f32> {
         self.q_parse(format!(":OUTP:OCP:VAL? CH{ch}\n").as_str())
     }
 
     /// Set the over current protection value in Amps.
-    pub fn set_ocp(&mut self, ch: u8, amps: f32) -> io::Result<()> {
-        self.cmd(format!(":OUTP:OCP:VAL CH{ch},{amps:.3}\n").as_str())
+    ///
+    /// The current is rounded to the resolution of the model, the current
+    /// applied is returned.
+    pub fn set_ocp(&mut self, ch: u8, amps: impl Into<Amps>) -> io::Result<Amps> {
+        let amps: String = self.format_current(amps.into())?;
+        self.cmd(format!(":OUTP:OCP:VAL CH{ch},{amps}\n").as_str())?;
+        parse(amps.as_str()).map(Amps)
     }
 
     /// Returns `true` if over current protection is enabled.
@@ -255,8 +643,13 @@ impl Dp800 {
     }
 
     /// Set the over voltage protection value in Volts.
-    pub fn set_ovp(&mut self, ch: u8, volts: f32) -> io::Result<()> {
-        self.cmd(format!(":OUTP:OVP:VAL CH{ch},{volts:.3}\n").as_str())
+    ///
+    /// The voltage is rounded to the resolution of the model, the voltage
+    /// applied is returned.
+    pub fn set_ovp(&mut self, ch: u8, volts: impl Into<Volts>) -> io::Result<Volts> {
+        let volts: String = self.format_voltage(volts.into())?;
+        self.cmd(format!(":OUTP:OVP:VAL CH{ch},{volts}\n").as_str())?;
+        parse(volts.as_str()).map(Volts)
     }
 
     /// Returns `true` if over voltage protection is enabled.
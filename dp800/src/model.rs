@@ -0,0 +1,125 @@
+use crate::parse_error;
+use std::{io, str::FromStr};
+
+/// DP800 series model.
+///
+/// Returned by [`Dp800::model`](crate::Dp800::model).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "UPPERCASE"))]
+pub enum Model {
+    Dp811,
+    Dp811A,
+    Dp813,
+    Dp813A,
+    Dp821,
+    Dp821A,
+    Dp831,
+    Dp831A,
+    Dp832,
+    Dp832A,
+}
+
+impl Model {
+    /// Number of output channels.
+    pub fn num_channels(&self) -> u8 {
+        match self {
+            Self::Dp811 | Self::Dp811A | Self::Dp813 | Self::Dp813A => 1,
+            Self::Dp821 | Self::Dp821A => 2,
+            Self::Dp831 | Self::Dp831A | Self::Dp832 | Self::Dp832A => 3,
+        }
+    }
+
+    /// Returns `true` if the channel supports tracking.
+    pub fn has_tracking(&self, ch: u8) -> bool {
+        match self {
+            Self::Dp831 | Self::Dp831A => matches!(ch, 2 | 3),
+            Self::Dp832 | Self::Dp832A => matches!(ch, 1 | 2),
+            Self::Dp811
+            | Self::Dp811A
+            | Self::Dp813
+            | Self::Dp813A
+            | Self::Dp821
+            | Self::Dp821A => false,
+        }
+    }
+
+    /// Number of decimal places of the voltage setting resolution.
+    ///
+    /// The A variants have a resolution of 1 mV, the others 10 mV.
+    pub fn voltage_decimals(&self) -> usize {
+        if self.is_high_resolution() { 3 } else { 2 }
+    }
+
+    /// Number of decimal places of the current setting resolution.
+    ///
+    /// The A variants have a resolution of 0.1 mA, the others 1 mA.
+    pub fn current_decimals(&self) -> usize {
+        if self.is_high_resolution() { 4 } else { 3 }
+    }
+
+    fn is_high_resolution(&self) -> bool {
+        matches!(
+            self,
+            Self::Dp811A | Self::Dp813A | Self::Dp821A | Self::Dp831A | Self::Dp832A
+        )
+    }
+
+    /// Round a voltage to the setting resolution.
+    pub fn round_voltage(&self, volts: f32) -> f32 {
+        round(volts, self.voltage_decimals())
+    }
+
+    /// Round a current to the setting resolution.
+    pub fn round_current(&self, amps: f32) -> f32 {
+        round(amps, self.current_decimals())
+    }
+
+    /// Iterator over the 1-indexed output channels.
+    pub fn channels(&self) -> impl Iterator<Item = u8> + use<> {
+        1..=self.num_channels()
+    }
+}
+
+/// Round a value to a number of decimal places, as formatted in commands.
+fn round(value: f32, decimals: usize) -> f32 {
+    format!("{value:.decimals$}").parse().unwrap_or(value)
+}
+
+impl FromStr for Model {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "DP811" => Ok(Self::Dp811),
+            "DP811A" => Ok(Self::Dp811A),
+            "DP813" => Ok(Self::Dp813),
+            "DP813A" => Ok(Self::Dp813A),
+            "DP821" => Ok(Self::Dp821),
+            "DP821A" => Ok(Self::Dp821A),
+            "DP831" => Ok(Self::Dp831),
+            "DP831A" => Ok(Self::Dp831A),
+            "DP832" => Ok(Self::Dp832),
+            "DP832A" => Ok(Self::Dp832A),
+            _ => Err(parse_error()),
+        }
+    }
+}
+
+impl std::fmt::Display for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s: &str = match self {
+            Self::Dp811 => "DP811",
+            Self::Dp811A => "DP811A",
+            Self::Dp813 => "DP813",
+            Self::Dp813A => "DP813A",
+            Self::Dp821 => "DP821",
+            Self::Dp821A => "DP821A",
+            Self::Dp831 => "DP831",
+            Self::Dp831A => "DP831A",
+            Self::Dp832 => "DP832",
+            Self::Dp832A => "DP832A",
+        };
+        write!(f, "{s}")
+    }
+}
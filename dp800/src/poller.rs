@@ -0,0 +1,301 @@
+use crate::{Sample, SharedDp800};
+use std::{
+    collections::VecDeque,
+    io,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, Weak},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+struct QueueState {
+    items: VecDeque<Sample>,
+    closed: bool,
+    dropped: u64,
+}
+
+/// Bounded queue that drops the oldest sample when full.
+struct Queue {
+    state: Mutex<QueueState>,
+    ready: Condvar,
+    capacity: usize,
+}
+
+impl Queue {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(&self, sample: Sample) {
+        let mut state = self.lock();
+        if state.items.len() == self.capacity {
+            state.items.pop_front();
+            state.dropped += 1;
+        }
+        state.items.push_back(sample);
+        self.ready.notify_one();
+    }
+
+    fn close(&self) {
+        self.lock().closed = true;
+        self.ready.notify_all();
+    }
+}
+
+/// Stream of samples from a [`Poller`].
+///
+/// Created by [`Poller::subscribe`].
+/// Iterating blocks until the next sample, and ends when the poller stops.
+pub struct Subscription {
+    queue: Arc<Queue>,
+}
+
+impl Subscription {
+    /// Wait for the next sample.
+    ///
+    /// Returns `None` if the poller has stopped and no samples are queued.
+    pub fn recv(&self) -> Option<Sample> {
+        let mut state = self.queue.lock();
+        loop {
+            if let Some(sample) = state.items.pop_front() {
+                return Some(sample);
+            }
+            if state.closed {
+                return None;
+            }
+            state = self
+                .queue
+                .ready
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Wait for the next sample for at most `timeout`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Sample> {
+        let deadline: Instant = Instant::now() + timeout;
+        let mut state = self.queue.lock();
+        loop {
+            if let Some(sample) = state.items.pop_front() {
+                return Some(sample);
+            }
+            let now: Instant = Instant::now();
+            if state.closed || now >= deadline {
+                return None;
+            }
+            state = self
+                .queue
+                .ready
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    /// Get the next sample without waiting.
+    pub fn try_recv(&self) -> Option<Sample> {
+        self.queue.lock().items.pop_front()
+    }
+
+    /// Number of samples dropped because the subscription was full.
+    pub fn dropped(&self) -> u64 {
+        self.queue.lock().dropped
+    }
+}
+
+impl Iterator for Subscription {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv()
+    }
+}
+
+struct Shared {
+    /// Subscriber queues, `None` after the poller thread has exited.
+    subscribers: Mutex<Option<Vec<Weak<Queue>>>>,
+    stopped: Mutex<bool>,
+    stop: Condvar,
+}
+
+impl Shared {
+    fn subscribers(&self) -> MutexGuard<'_, Option<Vec<Weak<Queue>>>> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Send a sample to every subscriber, removing dropped subscriptions.
+    fn publish(&self, sample: Sample) {
+        if let Some(subscribers) = self.subscribers().as_mut() {
+            subscribers.retain(|queue| match queue.upgrade() {
+                Some(queue) => {
+                    queue.push(sample.clone());
+                    true
+                }
+                None => false,
+            });
+        }
+    }
+
+    /// Wait until `deadline`, returns `true` if the poller was stopped.
+    fn wait_until(&self, deadline: Instant) -> bool {
+        let mut stopped = self.stopped.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            let now: Instant = Instant::now();
+            if *stopped || now >= deadline {
+                return *stopped;
+            }
+            stopped = self
+                .stop
+                .wait_timeout(stopped, deadline - now)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+}
+
+/// Background measurement poller.
+///
+/// Measures a set of channels at a fixed interval on its own thread and
+/// sends every sample to each [`Subscription`].
+/// All channels are measured in a single round-trip on a [`SharedDp800`],
+/// so the instrument can still be controlled from other threads.
+///
+/// Each subscription buffers a limited number of samples, when a
+/// subscriber falls behind the oldest samples are dropped.
+///
+/// Measurement errors are logged and polling continues at the next
+/// interval, except [`io::ErrorKind::BrokenPipe`] errors, returned after
+/// the [`SharedDp800`] thread has exited, which stop the poller and end
+/// every subscription.
+/// The poller stops when dropped.
+pub struct Poller {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Poller {
+    /// Start measuring `channels` every `interval`.
+    pub fn start(dp800: SharedDp800, channels: &[u8], interval: Duration) -> io::Result<Self> {
+        let shared: Arc<Shared> = Arc::new(Shared {
+            subscribers: Mutex::new(Some(Vec::new())),
+            stopped: Mutex::new(false),
+            stop: Condvar::new(),
+        });
+        let channels: Vec<u8> = channels.to_vec();
+        let thread_shared: Arc<Shared> = Arc::clone(&shared);
+        let thread: JoinHandle<()> = std::thread::Builder::new()
+            .name("dp800-poller".to_string())
+            .spawn(move || Self::run(&dp800, &channels, interval, &thread_shared))?;
+        Ok(Self {
+            shared,
+            thread: Some(thread),
+        })
+    }
+
+    fn run(dp800: &SharedDp800, channels: &[u8], interval: Duration, shared: &Shared) {
+        let mut deadline: Instant = Instant::now();
+        loop {
+            let query_channels: Vec<u8> = channels.to_vec();
+            match dp800.with(move |dp800| dp800.sample_channels(&query_channels)) {
+                Ok(samples) => samples
+                    .into_iter()
+                    .for_each(|sample| shared.publish(sample)),
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                    log::error!("Stopped polling measurements: {e}");
+                    break;
+                }
+                Err(e) => log::warn!("Failed to poll measurements: {e}"),
+            }
+
+            deadline += interval;
+            // skip missed intervals instead of polling in a burst
+            let now: Instant = Instant::now();
+            if deadline < now {
+                deadline = now;
+            }
+            if shared.wait_until(deadline) {
+                break;
+            }
+        }
+
+        for queue in shared.subscribers().take().into_iter().flatten() {
+            if let Some(queue) = queue.upgrade() {
+                queue.close();
+            }
+        }
+    }
+
+    /// Subscribe to samples, buffering at most `capacity` samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn subscribe(&self, capacity: usize) -> Subscription {
+        assert!(capacity > 0, "capacity must be greater than zero");
+        let queue: Arc<Queue> = Arc::new(Queue {
+            state: Mutex::new(QueueState {
+                items: VecDeque::with_capacity(capacity),
+                closed: false,
+                dropped: 0,
+            }),
+            ready: Condvar::new(),
+            capacity,
+        });
+        match self.shared.subscribers().as_mut() {
+            Some(subscribers) => subscribers.push(Arc::downgrade(&queue)),
+            None => queue.close(),
+        }
+        Subscription { queue }
+    }
+
+    /// Stop polling and wait for the poller thread to exit.
+    ///
+    /// Subscriptions end after the remaining samples are received.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        *self
+            .shared
+            .stopped
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = true;
+        self.shared.stop.notify_all();
+        if let Some(thread) = self.thread.take() {
+            // a panic has already been reported by the thread
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dp800Builder, Replay};
+
+    #[test]
+    fn thread_exited() {
+        let replay: Replay = "".parse().unwrap();
+        let dp800: SharedDp800 = SharedDp800::new(Dp800Builder::new().build(replay)).unwrap();
+        let err: io::Error = dp800
+            .with(|_| -> io::Result<()> { panic!("instrument thread panic") })
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+
+        let poller: Poller = Poller::start(dp800, &[1], Duration::from_millis(1)).unwrap();
+        let subscription: Subscription = poller.subscribe(1);
+        assert!(subscription.recv().is_none());
+
+        // subscriptions after the poller stopped end immediately
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(poller.subscribe(1).recv().is_none());
+    }
+}
@@ -0,0 +1,311 @@
+use crate::{ChannelState, Dp800, Model};
+use std::{io, path::Path, str::FromStr};
+
+/// Configuration of a single channel in a [`Profile`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChannelProfile {
+    /// Channel, 1-indexed.
+    pub channel: u8,
+    /// Voltage setpoint in Volts.
+    pub voltage: f32,
+    /// Current setpoint in Amps.
+    pub current: f32,
+    /// Over voltage protection value in Volts, `None` to disable over voltage
+    /// protection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ovp: Option<f32>,
+    /// Over current protection value in Amps, `None` to disable over current
+    /// protection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocp: Option<f32>,
+}
+
+/// Device under test configuration profile.
+///
+/// Profiles are stored as TOML, for example:
+///
+/// ```toml
+/// name = "Widget rev B"
+///
+/// [[channel]]
+/// channel = 1
+/// voltage = 3.3
+/// current = 0.5
+/// ovp = 3.6
+/// ocp = 0.6
+///
+/// [[channel]]
+/// channel = 2
+/// voltage = 5.0
+/// current = 1.0
+/// ```
+///
+/// Channels not listed in the profile are left untouched, each channel may
+/// be listed once.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Profile name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Channel configurations.
+    #[serde(default, rename = "channel")]
+    pub channels: Vec<ChannelProfile>,
+}
+
+/// Difference between a [`Profile`] and the instrument settings.
+///
+/// Returned by [`Profile::verify`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mismatch {
+    /// Voltage setpoint in Volts.
+    Voltage {
+        channel: u8,
+        expected: f32,
+        actual: f32,
+    },
+    /// Current setpoint in Amps.
+    Current {
+        channel: u8,
+        expected: f32,
+        actual: f32,
+    },
+    /// Over voltage protection value in Volts, `None` if disabled.
+    Ovp {
+        channel: u8,
+        expected: Option<f32>,
+        actual: Option<f32>,
+    },
+    /// Over current protection value in Amps, `None` if disabled.
+    Ocp {
+        channel: u8,
+        expected: Option<f32>,
+        actual: Option<f32>,
+    },
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let protection = |value: &Option<f32>, unit: &str| match value {
+            Some(value) => format!("{value:.3} {unit}"),
+            None => "off".to_string(),
+        };
+
+        match self {
+            Self::Voltage {
+                channel,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "CH{channel} voltage: expected {expected:.3} V, found {actual:.3} V"
+            ),
+            Self::Current {
+                channel,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "CH{channel} current: expected {expected:.3} A, found {actual:.3} A"
+            ),
+            Self::Ovp {
+                channel,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "CH{channel} OVP: expected {}, found {}",
+                protection(expected, "V"),
+                protection(actual, "V")
+            ),
+            Self::Ocp {
+                channel,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "CH{channel} OCP: expected {}, found {}",
+                protection(expected, "A"),
+                protection(actual, "A")
+            ),
+        }
+    }
+}
+
+/// Maximum difference between a profile value and the instrument setting.
+const TOLERANCE: f32 = 0.0005;
+
+fn matches(expected: f32, actual: f32) -> bool {
+    (expected - actual).abs() <= TOLERANCE
+}
+
+fn matches_protection(expected: Option<f32>, actual: Option<f32>) -> bool {
+    match (expected, actual) {
+        (Some(expected), Some(actual)) => matches(expected, actual),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+impl FromStr for Profile {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl Profile {
+    /// Load a profile from a TOML file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Serialize the profile to TOML.
+    pub fn to_toml(&self) -> io::Result<String> {
+        toml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn check_channels(&self, dp800: &mut Dp800) -> io::Result<()> {
+        let num_channels: u8 = dp800.model()?.num_channels();
+        for (idx, ch_profile) in self.channels.iter().enumerate() {
+            if self.channels[..idx]
+                .iter()
+                .any(|other| other.channel == ch_profile.channel)
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Profile channel {} is listed more than once",
+                        ch_profile.channel
+                    ),
+                ));
+            }
+            if !(1..=num_channels).contains(&ch_profile.channel) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Profile channel {} does not exist, instrument has {num_channels} channels",
+                        ch_profile.channel
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the profile to the instrument.
+    ///
+    /// Setpoints above the profile setpoints are lowered first, then
+    /// protection settings are applied, then the setpoints are set.
+    /// This prevents the new protection values from tripping an output that
+    /// is on.
+    /// Output states are not changed.
+    pub fn apply(&self, dp800: &mut Dp800) -> io::Result<()> {
+        self.check_channels(dp800)?;
+
+        for ch_profile in self.channels.iter() {
+            dp800.lower_setpoints(ch_profile.channel, ch_profile.voltage, ch_profile.current)?;
+        }
+
+        for ch_profile in self.channels.iter() {
+            let ch: u8 = ch_profile.channel;
+            if let Some(ovp) = ch_profile.ovp {
+                dp800.set_ovp(ch, ovp)?;
+            }
+            dp800.set_ovp_on(ch, ch_profile.ovp.is_some())?;
+            if let Some(ocp) = ch_profile.ocp {
+                dp800.set_ocp(ch, ocp)?;
+            }
+            dp800.set_ocp_on(ch, ch_profile.ocp.is_some())?;
+        }
+
+        for ch_profile in self.channels.iter() {
+            dp800.set_voltage(ch_profile.channel, ch_profile.voltage)?;
+            dp800.set_current(ch_profile.channel, ch_profile.current)?;
+        }
+
+        Ok(())
+    }
+
+    /// Compare the profile to the instrument settings.
+    ///
+    /// Profile values are rounded to the resolution of the model before
+    /// comparing.
+    ///
+    /// Returns every mismatch, an empty vector indicates the instrument
+    /// matches the profile.
+    pub fn verify(&self, dp800: &mut Dp800) -> io::Result<Vec<Mismatch>> {
+        self.check_channels(dp800)?;
+        let model: Model = dp800.model()?;
+
+        let mut mismatches: Vec<Mismatch> = Vec::new();
+        for ch_profile in self.channels.iter() {
+            let channel: u8 = ch_profile.channel;
+            let state: ChannelState = dp800.channel_state(channel)?;
+
+            if !matches(model.round_voltage(ch_profile.voltage), state.voltage) {
+                mismatches.push(Mismatch::Voltage {
+                    channel,
+                    expected: ch_profile.voltage,
+                    actual: state.voltage,
+                });
+            }
+            if !matches(model.round_current(ch_profile.current), state.current) {
+                mismatches.push(Mismatch::Current {
+                    channel,
+                    expected: ch_profile.current,
+                    actual: state.current,
+                });
+            }
+
+            let ovp: Option<f32> = state.ovp_on.then_some(state.ovp);
+            if !matches_protection(ch_profile.ovp.map(|v| model.round_voltage(v)), ovp) {
+                mismatches.push(Mismatch::Ovp {
+                    channel,
+                    expected: ch_profile.ovp,
+                    actual: ovp,
+                });
+            }
+            let ocp: Option<f32> = state.ocp_on.then_some(state.ocp);
+            if !matches_protection(ch_profile.ocp.map(|a| model.round_current(a)), ocp) {
+                mismatches.push(Mismatch::Ocp {
+                    channel,
+                    expected: ch_profile.ocp,
+                    actual: ocp,
+                });
+            }
+        }
+
+        Ok(mismatches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dp800Builder, Replay};
+
+    #[test]
+    fn duplicate_channel() {
+        let profile: Profile = "[[channel]]\n\
+                                channel = 1\n\
+                                voltage = 12.0\n\
+                                current = 1.0\n\
+                                [[channel]]\n\
+                                channel = 1\n\
+                                voltage = 3.3\n\
+                                current = 1.0\n"
+            .parse()
+            .unwrap();
+        let replay: Replay = "> *IDN?\n\
+                              < RIGOL TECHNOLOGIES,DP832,DP8C000000000,00.01.16\n"
+            .parse()
+            .unwrap();
+        let mut dp800: Dp800 = Dp800Builder::new().build(replay.clone());
+        let err: io::Error = profile.apply(&mut dp800).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        replay.finish().unwrap();
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{Amps, Dp800, OutputMode, Volts};
+use std::{
+    io,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// Maximum time between checks of the cancel token while waiting for the
+/// next step.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+fn invalid_input(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+/// Settings for [`Dp800::ramp_voltage`] and [`Dp800::ramp_current`].
+#[derive(Debug, Clone)]
+pub struct Ramp {
+    rate: f32,
+    step: f32,
+    cancel: Option<Arc<AtomicBool>>,
+    abort_on_cc: bool,
+}
+
+impl Ramp {
+    /// Ramp at `rate` per second in steps of `step`, both in Volts for a
+    /// voltage ramp or Amps for a current ramp.
+    ///
+    /// Steps smaller than the resolution of the model are rounded.
+    pub fn new(rate: f32, step: f32) -> Self {
+        Self {
+            rate,
+            step,
+            cancel: None,
+            abort_on_cc: false,
+        }
+    }
+
+    /// Stop the ramp when `cancel` is set to `true`.
+    ///
+    /// The token is checked before every step, and every 10 ms while waiting
+    /// for the next step.
+    #[must_use]
+    pub fn cancel_token(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Stop the ramp if the channel enters constant current mode.
+    ///
+    /// This takes an additional query after every step.
+    #[must_use]
+    pub fn abort_on_cc(mut self, abort: bool) -> Self {
+        self.abort_on_cc = abort;
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    /// Sleep until `deadline`, returning `true` if cancelled while sleeping.
+    fn sleep_until(&self, deadline: Instant) -> bool {
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            if self.is_cancelled() {
+                return true;
+            }
+            std::thread::sleep(remaining.min(CANCEL_POLL_INTERVAL));
+        }
+        self.is_cancelled()
+    }
+}
+
+/// Result of a ramp.
+///
+/// `reached` is the last setpoint applied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RampOutcome {
+    /// The target was reached.
+    Completed { reached: f32 },
+    /// The ramp was cancelled with the cancel token.
+    Cancelled { reached: f32 },
+    /// The channel entered constant current mode.
+    ConstantCurrent { reached: f32 },
+}
+
+type Setter = fn(&mut Dp800, u8, f32) -> io::Result<f32>;
+
+impl Dp800 {
+    /// Step the voltage setpoint from the present setpoint to `target`.
+    ///
+    /// This blocks until the ramp completes, is cancelled, or is aborted.
+    pub fn ramp_voltage(
+        &mut self,
+        ch: u8,
+        target: impl Into<Volts>,
+        ramp: &Ramp,
+    ) -> io::Result<RampOutcome> {
+        let Volts(target) = target.into();
+        let start: f32 = self.voltage(ch)?;
+        self.ramp(ch, start, target, ramp, |dp800, ch, volts| {
+            dp800.set_voltage(ch, volts).map(f32::from)
+        })
+    }
+
+    /// Step the current setpoint from the present setpoint to `target`.
+    ///
+    /// This blocks until the ramp completes, is cancelled, or is aborted.
+    pub fn ramp_current(
+        &mut self,
+        ch: u8,
+        target: impl Into<Amps>,
+        ramp: &Ramp,
+    ) -> io::Result<RampOutcome> {
+        let Amps(target) = target.into();
+        let start: f32 = self.current(ch)?;
+        self.ramp(ch, start, target, ramp, |dp800, ch, amps| {
+            dp800.set_current(ch, amps).map(f32::from)
+        })
+    }
+
+    fn ramp(
+        &mut self,
+        ch: u8,
+        start: f32,
+        target: f32,
+        ramp: &Ramp,
+        set: Setter,
+    ) -> io::Result<RampOutcome> {
+        if !(ramp.rate > 0.0 && ramp.step > 0.0 && ramp.step.is_finite()) {
+            return Err(invalid_input(
+                "Ramp rate and step must be greater than zero, and step must be finite",
+            ));
+        }
+        if !target.is_finite() {
+            return Err(invalid_input("Ramp target must be finite"));
+        }
+
+        let distance: f32 = (target - start).abs();
+        let direction: f32 = (target - start).signum();
+        let steps: f32 = (distance / ramp.step).ceil();
+        if steps > u32::MAX as f32 {
+            return Err(invalid_input("Ramp has too many steps"));
+        }
+        let steps: u32 = steps as u32;
+        let interval: Duration = Duration::try_from_secs_f32(ramp.step / ramp.rate)
+            .map_err(|_| invalid_input("Ramp step interval is out of range"))?;
+        let begin: Instant = Instant::now();
+        // every deadline is before the last, so checking the last is enough
+        interval
+            .checked_mul(steps)
+            .and_then(|duration| begin.checked_add(duration))
+            .ok_or_else(|| invalid_input("Ramp duration is out of range"))?;
+        let mut reached: f32 = start;
+
+        for idx in 1..=steps {
+            if ramp.is_cancelled() {
+                return Ok(RampOutcome::Cancelled { reached });
+            }
+
+            let offset: f32 = (idx as f32 * ramp.step).min(distance);
+            reached = set(self, ch, start + direction * offset)?;
+
+            if ramp.abort_on_cc && self.output_mode(ch)? == OutputMode::ConstantCurrent {
+                return Ok(RampOutcome::ConstantCurrent { reached });
+            }
+
+            if idx != steps {
+                // scheduled from the start to avoid accumulating drift
+                if ramp.sleep_until(begin + interval * idx) {
+                    return Ok(RampOutcome::Cancelled { reached });
+                }
+            }
+        }
+
+        Ok(RampOutcome::Completed { reached })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dp800Builder, Replay};
+
+    fn ramp_err(target: f32, ramp: &Ramp) -> io::ErrorKind {
+        let replay: Replay = "> :SOUR1:VOLT?\n< 0.000\n".parse().unwrap();
+        let mut dp800: Dp800 = Dp800Builder::new().build(replay.clone());
+        let kind: io::ErrorKind = dp800.ramp_voltage(1, target, ramp).unwrap_err().kind();
+        replay.finish().unwrap();
+        kind
+    }
+
+    #[test]
+    fn invalid_ramp() {
+        for (target, ramp) in [
+            (5.0, Ramp::new(0.0, 0.1)),
+            (5.0, Ramp::new(1.0, 0.0)),
+            (5.0, Ramp::new(f32::NAN, 0.1)),
+            (5.0, Ramp::new(1.0, f32::INFINITY)),
+            (5.0, Ramp::new(1e-38, 1.0)),
+            (5.0, Ramp::new(1.0, 1e-38)),
+            (5.0, Ramp::new(1e-22, 1e-3)),
+            (f32::NAN, Ramp::new(1.0, 0.1)),
+            (f32::INFINITY, Ramp::new(1.0, 0.1)),
+        ] {
+            assert_eq!(
+                ramp_err(target, &ramp),
+                io::ErrorKind::InvalidInput,
+                "{target} {ramp:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn cancel_while_waiting() {
+        let replay: Replay = "> :SOUR1:VOLT?\n\
+                              < 0.000\n\
+                              > *IDN?\n\
+                              < RIGOL TECHNOLOGIES,DP832,DP8C000000000,00.01.16\n\
+                              > :SOUR1:VOLT 1.00\n"
+            .parse()
+            .unwrap();
+        let mut dp800: Dp800 = Dp800Builder::new().build(replay.clone());
+        let cancel: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let ramp: Ramp = Ramp::new(0.1, 1.0).cancel_token(cancel.clone());
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            cancel.store(true, Ordering::Relaxed);
+        });
+        let start: Instant = Instant::now();
+        let outcome: RampOutcome = dp800.ramp_voltage(1, 5.0, &ramp).unwrap();
+        canceller.join().unwrap();
+        assert_eq!(outcome, RampOutcome::Cancelled { reached: 1.0 });
+        assert!(start.elapsed() < Duration::from_secs(1));
+        replay.finish().unwrap();
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{Dp800, Dp800Builder};
+use std::{
+    io,
+    net::{SocketAddr, ToSocketAddrs},
+    time::Duration,
+};
+
+/// Reconnection backoff settings for [`Reconnecting`].
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    /// Delay before the first reconnection attempt.
+    pub initial: Duration,
+    /// Maximum delay between reconnection attempts.
+    pub max: Duration,
+    /// Factor the delay is multiplied by after each failed attempt.
+    pub multiplier: u32,
+    /// Maximum number of reconnection attempts, `None` for no limit.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(5),
+            multiplier: 2,
+            max_attempts: Some(10),
+        }
+    }
+}
+
+/// Connection event passed to the hook registered with
+/// [`Reconnecting::on_event`].
+#[derive(Debug)]
+pub enum ConnectionEvent<'a> {
+    /// The connection was lost.
+    Lost(&'a io::Error),
+    /// A reconnection attempt failed.
+    ReconnectFailed { attempt: u32, error: &'a io::Error },
+    /// The connection was restored.
+    Restored,
+}
+
+type Hook = Box<dyn FnMut(&ConnectionEvent) + Send>;
+
+/// Returns `true` if the error indicates the connection was lost.
+///
+/// Read timeouts are not considered connection loss, they are handled by
+/// [`Dp800::resync`].
+fn is_connection_lost(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::UnexpectedEof
+    )
+}
+
+/// DP800 power supply connection that reconnects automatically.
+///
+/// Operations are passed as closures operating on the underlying [`Dp800`].
+/// When an operation fails because the connection was lost the wrapper
+/// reconnects with the configured [`Backoff`].
+///
+/// * Operations run with [`Reconnecting::retry`] are retried once after the
+///   connection is restored, they must be idempotent
+/// * Operations run with [`Reconnecting::run`] are never retried, the error
+///   is returned after the connection is restored
+///
+/// The instrument retains its settings when the connection is lost, no
+/// settings are re-applied after reconnecting.
+pub struct Reconnecting {
+    addrs: Vec<SocketAddr>,
+    builder: Dp800Builder,
+    dp800: Option<Dp800>,
+    backoff: Backoff,
+    hook: Option<Hook>,
+}
+
+impl Reconnecting {
+    /// Connect to the power supply with the default settings.
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Self::connect_with(Dp800Builder::new(), addr)
+    }
+
+    /// Connect to the power supply with the settings from `builder`.
+    ///
+    /// The same settings are used when reconnecting.
+    pub fn connect_with<A: ToSocketAddrs>(builder: Dp800Builder, addr: A) -> io::Result<Self> {
+        let addrs: Vec<SocketAddr> = addr.to_socket_addrs()?.collect();
+        let dp800: Dp800 = builder.connect(addrs.as_slice())?;
+        Ok(Self {
+            addrs,
+            builder,
+            dp800: Some(dp800),
+            backoff: Backoff::default(),
+            hook: None,
+        })
+    }
+
+    /// Set the reconnection backoff.
+    #[must_use]
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Register a hook called on connection loss and restoration.
+    #[must_use]
+    pub fn on_event<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(&ConnectionEvent) + Send + 'static,
+    {
+        self.hook = Some(Box::new(hook));
+        self
+    }
+
+    fn notify(&mut self, event: ConnectionEvent) {
+        if let Some(hook) = self.hook.as_mut() {
+            hook(&event)
+        }
+    }
+
+    fn reconnect(&mut self) -> io::Result<&mut Dp800> {
+        let mut delay: Duration = self.backoff.initial;
+        let mut attempt: u32 = 0;
+        loop {
+            std::thread::sleep(delay);
+            attempt += 1;
+            match self.builder.connect(self.addrs.as_slice()) {
+                Ok(dp800) => {
+                    self.notify(ConnectionEvent::Restored);
+                    return Ok(self.dp800.insert(dp800));
+                }
+                Err(error) => {
+                    self.notify(ConnectionEvent::ReconnectFailed {
+                        attempt,
+                        error: &error,
+                    });
+                    if self
+                        .backoff
+                        .max_attempts
+                        .is_some_and(|max_attempts| attempt >= max_attempts)
+                    {
+                        return Err(error);
+                    }
+                }
+            }
+            delay = delay
+                .saturating_mul(self.backoff.multiplier)
+                .min(self.backoff.max);
+        }
+    }
+
+    /// Underlying connection, reconnecting if the connection was lost.
+    pub fn get_mut(&mut self) -> io::Result<&mut Dp800> {
+        match self.dp800 {
+            Some(ref mut dp800) => Ok(dp800),
+            None => self.reconnect(),
+        }
+    }
+
+    /// Underlying connection, or `None` if the connection was lost and has
+    /// not been restored.
+    ///
+    /// Unlike [`Reconnecting::get_mut`] this never reconnects.
+    pub fn connection(&mut self) -> Option<&mut Dp800> {
+        self.dp800.as_mut()
+    }
+
+    /// Drop the lost connection and reconnect.
+    fn recover(&mut self, error: &io::Error) -> io::Result<()> {
+        self.dp800 = None;
+        self.notify(ConnectionEvent::Lost(error));
+        self.reconnect()?;
+        Ok(())
+    }
+
+    /// Run an idempotent operation, retrying it once if the connection was
+    /// lost.
+    ///
+    /// Queries and commands setting absolute values, such as
+    /// [`Dp800::set_voltage`], are idempotent.
+    pub fn retry<T, F>(&mut self, mut f: F) -> io::Result<T>
+    where
+        F: FnMut(&mut Dp800) -> io::Result<T>,
+    {
+        match f(self.get_mut()?) {
+            Err(e) if is_connection_lost(&e) => {
+                self.recover(&e)?;
+                f(self.get_mut()?)
+            }
+            result => result,
+        }
+    }
+
+    /// Run an operation without retrying it if the connection was lost.
+    pub fn run<T, F>(&mut self, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut Dp800) -> io::Result<T>,
+    {
+        let result: io::Result<T> = f(self.get_mut()?);
+        if let Err(e) = &result
+            && is_connection_lost(e)
+        {
+            self.recover(e)?;
+        }
+        result
+    }
+}
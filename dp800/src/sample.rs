@@ -0,0 +1,63 @@
+use crate::{Dp800, Measurement};
+use std::{
+    io,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Measurement with the time and source of the sample.
+///
+/// Returned by [`Dp800::sample`], and sent to subscribers of a
+/// [`Poller`](crate::Poller).
+///
+/// With the `serde` feature this only implements `Serialize`, and the
+/// monotonic timestamp is not serialized.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Sample {
+    /// Serial number of the instrument.
+    pub serial_number: String,
+    /// Channel the measurement was taken from.
+    pub channel: u8,
+    /// Wall-clock time the measurement was requested.
+    pub time: SystemTime,
+    /// Monotonic time the measurement was requested.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub instant: Instant,
+    /// Round-trip time of the measurement query.
+    pub latency: Duration,
+    /// Measured values.
+    pub measurement: Measurement,
+}
+
+impl Dp800 {
+    /// Get a measurement of a channel with sample metadata.
+    pub fn sample(&mut self, ch: u8) -> io::Result<Sample> {
+        self.sample_channels(&[ch])?
+            .pop()
+            .ok_or_else(crate::parse_error)
+    }
+
+    /// Get a measurement of each of the given channels with sample metadata.
+    ///
+    /// All channels are measured in a single round-trip, and share the same
+    /// timestamps and latency.
+    pub fn sample_channels(&mut self, channels: &[u8]) -> io::Result<Vec<Sample>> {
+        let serial_number: String = self.serial_number()?;
+        let time: SystemTime = SystemTime::now();
+        let instant: Instant = Instant::now();
+        let measurements: Vec<Measurement> = self.measure_channels(channels)?;
+        let latency: Duration = instant.elapsed();
+        Ok(channels
+            .iter()
+            .zip(measurements)
+            .map(|(&channel, measurement)| Sample {
+                serial_number: serial_number.clone(),
+                channel,
+                time,
+                instant,
+                latency,
+                measurement,
+            })
+            .collect())
+    }
+}
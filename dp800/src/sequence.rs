@@ -0,0 +1,242 @@
+use crate::{Dp800, Volts};
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+/// Interval between measurements while waiting for a [`Precondition`].
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Measured voltage a channel must reach before a [`Step`] runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Precondition {
+    /// Channel to measure, 1-indexed.
+    pub channel: u8,
+    /// Expected voltage in Volts.
+    pub voltage: f32,
+    /// Maximum difference from the expected voltage in Volts.
+    pub tolerance: f32,
+    /// Time to wait for the voltage to be within tolerance.
+    pub timeout: Duration,
+}
+
+impl Precondition {
+    /// Require the measured voltage of `channel` to be within `tolerance`
+    /// of `voltage`, waiting up to 100 ms.
+    pub fn voltage(channel: u8, voltage: impl Into<Volts>, tolerance: impl Into<Volts>) -> Self {
+        Self {
+            channel,
+            voltage: voltage.into().0,
+            tolerance: tolerance.into().0,
+            timeout: Duration::from_millis(100),
+        }
+    }
+
+    /// Set the time to wait for the voltage to be within tolerance.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Wait for the precondition, returning the last measured voltage if it
+    /// was not met.
+    fn wait(&self, dp800: &mut Dp800) -> io::Result<Result<(), f32>> {
+        let start: Instant = Instant::now();
+        loop {
+            let measured: f32 = dp800.measure_voltage(self.channel)?;
+            if (measured - self.voltage).abs() <= self.tolerance {
+                return Ok(Ok(()));
+            }
+            if start.elapsed() >= self.timeout {
+                return Ok(Err(measured));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Output of a single channel in a [`Sequence`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    /// Channel, 1-indexed.
+    pub channel: u8,
+    /// Delay after the previous step.
+    pub delay: Duration,
+    /// Conditions checked after the delay, before the output is enabled.
+    pub preconditions: Vec<Precondition>,
+}
+
+impl Step {
+    /// Enable the output of `channel` without delay or preconditions.
+    pub fn new(channel: u8) -> Self {
+        Self {
+            channel,
+            delay: Duration::ZERO,
+            preconditions: Vec::new(),
+        }
+    }
+
+    /// Set the delay after the previous step.
+    #[must_use]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Add a condition that must be met before the output is enabled.
+    #[must_use]
+    pub fn require(mut self, precondition: Precondition) -> Self {
+        self.preconditions.push(precondition);
+        self
+    }
+}
+
+/// Power sequence of multiple channels.
+///
+/// Outputs are enabled in order by [`Sequence::power_up`], and disabled in
+/// reverse order by [`Sequence::power_down`].
+/// Setpoints are not changed, configure them beforehand.
+///
+/// For example, a 3.3 V rail on channel 1, then a 1.8 V rail on channel 2
+/// after 10 ms, then a 5 V rail on channel 3 only if the 3.3 V rail is in
+/// regulation, is described with three steps where the last step requires
+/// `Precondition::voltage(1, 3.3, 0.1)`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sequence {
+    /// Steps in power up order.
+    pub steps: Vec<Step>,
+}
+
+impl Sequence {
+    /// Create an empty sequence.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a step to the end of the sequence.
+    #[must_use]
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Enable outputs in order.
+    ///
+    /// If a step fails the output of every channel in the sequence is
+    /// disabled, then every other output of the instrument, and the error
+    /// from the step is returned.
+    pub fn power_up(&self, dp800: &mut Dp800) -> io::Result<()> {
+        let result: io::Result<()> = self.run_power_up(dp800);
+        if let Err(e) = result.as_ref() {
+            log::warn!("Power sequence failed, disabling all outputs: {e}");
+            let channels: Vec<u8> = self.steps.iter().map(|step| step.channel).collect();
+            if let Err(rollback) = disable_all(dp800, &channels) {
+                log::error!("Failed to disable outputs: {rollback}");
+            }
+        }
+        result
+    }
+
+    fn run_power_up(&self, dp800: &mut Dp800) -> io::Result<()> {
+        for (idx, step) in self.steps.iter().enumerate() {
+            std::thread::sleep(step.delay);
+            for precondition in step.preconditions.iter() {
+                if let Err(measured) = precondition.wait(dp800)? {
+                    return Err(io::Error::other(format!(
+                        "Step {} (CH{}) precondition failed: CH{} measured {measured:.3} V, expected {:.3} ± {:.3} V",
+                        idx + 1,
+                        step.channel,
+                        precondition.channel,
+                        precondition.voltage,
+                        precondition.tolerance,
+                    )));
+                }
+            }
+            dp800.set_output_state(step.channel, true)?;
+        }
+        Ok(())
+    }
+
+    /// Disable outputs in reverse order.
+    ///
+    /// The delay between steps is the same as [`Sequence::power_up`], and
+    /// preconditions are not checked.
+    pub fn power_down(&self, dp800: &mut Dp800) -> io::Result<()> {
+        let mut delay: Duration = Duration::ZERO;
+        for step in self.steps.iter().rev() {
+            std::thread::sleep(delay);
+            dp800.set_output_state(step.channel, false)?;
+            delay = step.delay;
+        }
+        Ok(())
+    }
+}
+
+/// Disable the output of a channel, keeping the first error in `result`.
+fn disable(dp800: &mut Dp800, ch: u8, result: &mut io::Result<()>) {
+    if let Err(e) = dp800.set_output_state(ch, false) {
+        log::error!("Failed to disable CH{ch}: {e}");
+        if result.is_ok() {
+            *result = Err(e);
+        }
+    }
+}
+
+/// Disable the output of every channel in `channels`, then every other
+/// channel of the model if the model is supported.
+///
+/// Every channel is attempted, the first error is returned.
+fn disable_all(dp800: &mut Dp800, channels: &[u8]) -> io::Result<()> {
+    let mut result: io::Result<()> = Ok(());
+    for &ch in channels.iter() {
+        disable(dp800, ch, &mut result);
+    }
+    match dp800.model() {
+        Ok(model) => {
+            for ch in model.channels().filter(|ch| !channels.contains(ch)) {
+                disable(dp800, ch, &mut result);
+            }
+        }
+        Err(e) => log::warn!("Unable to disable channels not in the sequence: {e}"),
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dp800Builder, Replay};
+
+    fn power_up_rollback(idn: &str, rollback: &str) {
+        let replay: Replay = format!(
+            "> :OUTP CH1,ON\n\
+             > :MEAS:VOLT? CH1\n\
+             < 0.000\n\
+             > :OUTP CH1,OFF\n\
+             > :OUTP CH3,OFF\n\
+             > *IDN?\n\
+             < RIGOL TECHNOLOGIES,{idn},DP8C000000000,00.01.16\n\
+             {rollback}"
+        )
+        .parse()
+        .unwrap();
+        let mut dp800: Dp800 = Dp800Builder::new().build(replay.clone());
+        let sequence: Sequence = Sequence::new()
+            .step(Step::new(1))
+            .step(Step::new(3).require(Precondition::voltage(1, 3.3, 0.1).timeout(Duration::ZERO)));
+        let err: io::Error = sequence.power_up(&mut dp800).unwrap_err();
+        assert!(err.to_string().contains("precondition failed"), "{err}");
+        replay.finish().unwrap();
+    }
+
+    #[test]
+    fn rollback() {
+        power_up_rollback("DP832", "> :OUTP CH2,OFF\n");
+    }
+
+    #[test]
+    fn rollback_unsupported_model() {
+        power_up_rollback("DP899", "");
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{Dp800, Measurement};
+use std::{
+    io,
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+type Job = Box<dyn FnOnce(&mut Dp800) + Send>;
+
+fn thread_exited() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "Instrument thread has exited")
+}
+
+/// Cloneable handle to a [`Dp800`] shared between threads.
+///
+/// The [`Dp800`] is owned by a dedicated thread that executes requests one
+/// at a time in the order they were received.
+/// Each request runs to completion before the next one starts, so a request
+/// can send any number of commands and queries without other threads
+/// interleaving their own.
+///
+/// The thread exits when every handle has been dropped.
+/// If a request panics the thread exits, and this and all later requests
+/// return an [`io::ErrorKind::BrokenPipe`] error.
+#[derive(Clone)]
+pub struct SharedDp800 {
+    tx: Sender<Job>,
+}
+
+impl SharedDp800 {
+    /// Move a [`Dp800`] to a new thread and return a handle to it.
+    pub fn new(dp800: Dp800) -> io::Result<Self> {
+        let (tx, rx) = mpsc::channel::<Job>();
+        std::thread::Builder::new()
+            .name("dp800".to_string())
+            .spawn(move || Self::run(dp800, rx))?;
+        Ok(Self { tx })
+    }
+
+    fn run(mut dp800: Dp800, rx: Receiver<Job>) {
+        while let Ok(job) = rx.recv() {
+            job(&mut dp800);
+        }
+    }
+
+    /// Run a closure on the instrument thread, waiting for the result.
+    ///
+    /// No other request runs while the closure is running.
+    pub fn with<T, F>(&self, f: F) -> io::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Dp800) -> io::Result<T> + Send + 'static,
+    {
+        let (result_tx, result_rx) = mpsc::sync_channel::<io::Result<T>>(1);
+        self.tx
+            .send(Box::new(move |dp800| {
+                // the requesting thread may have exited
+                let _ = result_tx.send(f(dp800));
+            }))
+            .map_err(|_| thread_exited())?;
+        result_rx.recv().map_err(|_| thread_exited())?
+    }
+
+    /// Send a raw SCPI command, see [`Dp800::write_raw`].
+    pub fn write_raw(&self, msg: &str) -> io::Result<()> {
+        let msg: String = msg.to_string();
+        self.with(move |dp800| dp800.write_raw(&msg))
+    }
+
+    /// Send a raw SCPI query, see [`Dp800::query_raw`].
+    pub fn query_raw(&self, msg: &str) -> io::Result<String> {
+        let msg: String = msg.to_string();
+        self.with(move |dp800| dp800.query_raw(&msg))
+    }
+
+    /// Measure the voltage, current, and power of a channel.
+    pub fn measure(&self, ch: u8) -> io::Result<Measurement> {
+        self.with(move |dp800| dp800.measure(ch))
+    }
+}
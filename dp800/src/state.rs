@@ -0,0 +1,242 @@
+use crate::{Dp800, Measurement, Model, State, TrackMode, parse, parse_error};
+use std::io;
+
+/// Settings of a single channel.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChannelState {
+    /// Voltage setpoint in Volts.
+    pub voltage: f32,
+    /// Current setpoint in Amps.
+    pub current: f32,
+    /// Over voltage protection value in Volts.
+    pub ovp: f32,
+    /// Over voltage protection enabled.
+    pub ovp_on: bool,
+    /// Over current protection value in Amps.
+    pub ocp: f32,
+    /// Over current protection enabled.
+    pub ocp_on: bool,
+    /// Output enabled.
+    pub output: bool,
+    /// Tracking enabled, `None` if the channel does not support tracking.
+    pub tracking: Option<bool>,
+}
+
+/// Settings of the entire instrument.
+///
+/// Returned by [`Dp800::snapshot`], and applied with [`Dp800::restore`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstrumentState {
+    /// Channel settings, index 0 is channel 1.
+    pub channels: Vec<ChannelState>,
+    /// Track mode.
+    pub track_mode: TrackMode,
+}
+
+/// Settings and measurements of the entire instrument.
+///
+/// Returned by [`Dp800::status`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Status {
+    /// Instrument settings.
+    pub state: InstrumentState,
+    /// Channel measurements, index 0 is channel 1.
+    pub measurements: Vec<Measurement>,
+}
+
+fn push_channel_queries(queries: &mut Vec<String>, model: Model, ch: u8) {
+    queries.extend([
+        format!(":SOUR{ch}:VOLT?"),
+        format!(":SOUR{ch}:CURR?"),
+        format!(":OUTP:OVP:VAL? CH{ch}"),
+        format!(":OUTP:OVP:STAT? CH{ch}"),
+        format!(":OUTP:OCP:VAL? CH{ch}"),
+        format!(":OUTP:OCP:STAT? CH{ch}"),
+        format!(":OUTP? CH{ch}"),
+    ]);
+    if model.has_tracking(ch) {
+        queries.push(format!(":OUTP:TRAC? CH{ch}"));
+    }
+}
+
+fn next<'a, I: Iterator<Item = &'a String>>(responses: &mut I) -> io::Result<&'a str> {
+    responses.next().map(String::as_str).ok_or_else(parse_error)
+}
+
+fn next_bool<'a, I: Iterator<Item = &'a String>>(responses: &mut I) -> io::Result<bool> {
+    let state: State = parse(next(responses)?)?;
+    Ok(state.into())
+}
+
+fn parse_channel_state<'a, I: Iterator<Item = &'a String>>(
+    responses: &mut I,
+    model: Model,
+    ch: u8,
+) -> io::Result<ChannelState> {
+    Ok(ChannelState {
+        voltage: parse(next(responses)?)?,
+        current: parse(next(responses)?)?,
+        ovp: parse(next(responses)?)?,
+        ovp_on: next_bool(responses)?,
+        ocp: parse(next(responses)?)?,
+        ocp_on: next_bool(responses)?,
+        output: next_bool(responses)?,
+        tracking: if model.has_tracking(ch) {
+            Some(next_bool(responses)?)
+        } else {
+            None
+        },
+    })
+}
+
+impl Dp800 {
+    /// Read the settings of a single channel.
+    ///
+    /// All settings are read in a single round-trip.
+    pub fn channel_state(&mut self, ch: u8) -> io::Result<ChannelState> {
+        let model: Model = self.model()?;
+        let mut queries: Vec<String> = Vec::new();
+        push_channel_queries(&mut queries, model, ch);
+        let responses: Vec<String> = self.query_batch(&queries)?;
+        parse_channel_state(&mut responses.iter(), model, ch)
+    }
+
+    /// Read the settings of every channel.
+    ///
+    /// All settings are read in a single round-trip.
+    pub fn snapshot(&mut self) -> io::Result<InstrumentState> {
+        Ok(self.status_inner(false)?.state)
+    }
+
+    /// Read the settings and measurements of every channel.
+    ///
+    /// All settings and measurements are read in a single round-trip.
+    pub fn status(&mut self) -> io::Result<Status> {
+        self.status_inner(true)
+    }
+
+    fn status_inner(&mut self, measure: bool) -> io::Result<Status> {
+        let model: Model = self.model()?;
+
+        let mut queries: Vec<String> = vec![":SYST:TRACKM?".to_string()];
+        for ch in model.channels() {
+            push_channel_queries(&mut queries, model, ch);
+            if measure {
+                queries.push(format!(":MEAS:ALL? CH{ch}"));
+            }
+        }
+
+        let responses: Vec<String> = self.query_batch(&queries)?;
+        let mut responses = responses.iter();
+
+        let track_mode: TrackMode = parse(next(&mut responses)?)?;
+        let mut channels: Vec<ChannelState> = Vec::new();
+        let mut measurements: Vec<Measurement> = Vec::new();
+        for ch in model.channels() {
+            channels.push(parse_channel_state(&mut responses, model, ch)?);
+            if measure {
+                measurements.push(parse(next(&mut responses)?)?);
+            }
+        }
+
+        Ok(Status {
+            state: InstrumentState {
+                channels,
+                track_mode,
+            },
+            measurements,
+        })
+    }
+
+    /// Lower the setpoints of a channel that are above `voltage` or
+    /// `current`, setpoints below are not changed.
+    ///
+    /// This allows protection values for the new setpoints to be applied
+    /// without tripping the protection of a live output.
+    pub(crate) fn lower_setpoints(&mut self, ch: u8, voltage: f32, current: f32) -> io::Result<()> {
+        if self.voltage(ch)? > voltage {
+            self.set_voltage(ch, voltage)?;
+        }
+        if self.current(ch)? > current {
+            self.set_current(ch, current)?;
+        }
+        Ok(())
+    }
+
+    /// Apply settings read with [`Dp800::snapshot`].
+    ///
+    /// Settings are applied in an order that avoids transient output states:
+    ///
+    /// 1. Outputs that should be off are turned off
+    /// 2. Track mode is set, and tracking is disabled where required
+    /// 3. Setpoints of outputs that stay on are lowered where required
+    /// 4. Protection values and enables are set
+    /// 5. Setpoints are set
+    /// 6. Tracking is enabled where required
+    /// 7. Outputs that should be on are turned on
+    ///
+    /// Lowering setpoints before protection values, and raising them after,
+    /// prevents new protection values from tripping an output that is on.
+    pub fn restore(&mut self, state: &InstrumentState) -> io::Result<()> {
+        let num_channels: u8 = self.model()?.num_channels();
+        if state.channels.len() != usize::from(num_channels) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "State has {} channels, instrument has {num_channels}",
+                    state.channels.len()
+                ),
+            ));
+        }
+
+        let channels = || (1..=num_channels).zip(state.channels.iter());
+
+        for (ch, ch_state) in channels() {
+            if !ch_state.output {
+                self.set_output_state(ch, false)?;
+            }
+        }
+
+        self.set_track_mode(state.track_mode)?;
+        for (ch, ch_state) in channels() {
+            if ch_state.tracking == Some(false) {
+                self.set_tracking(ch, false)?;
+            }
+        }
+
+        for (ch, ch_state) in channels() {
+            if ch_state.output {
+                self.lower_setpoints(ch, ch_state.voltage, ch_state.current)?;
+            }
+        }
+
+        for (ch, ch_state) in channels() {
+            self.set_ovp(ch, ch_state.ovp)?;
+            self.set_ovp_on(ch, ch_state.ovp_on)?;
+            self.set_ocp(ch, ch_state.ocp)?;
+            self.set_ocp_on(ch, ch_state.ocp_on)?;
+        }
+
+        for (ch, ch_state) in channels() {
+            self.set_voltage(ch, ch_state.voltage)?;
+            self.set_current(ch, ch_state.current)?;
+        }
+
+        for (ch, ch_state) in channels() {
+            if ch_state.tracking == Some(true) {
+                self.set_tracking(ch, true)?;
+            }
+        }
+
+        for (ch, ch_state) in channels() {
+            if ch_state.output {
+                self.set_output_state(ch, true)?;
+            }
+        }
+
+        Ok(())
+    }
+}
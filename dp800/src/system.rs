@@ -0,0 +1,343 @@
+use crate::{Dp800, State, parse, parse_error};
+use std::{io, str::FromStr};
+
+/// Power-on state of the instrument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PowerOnState {
+    /// Restore the factory default settings at power-on.
+    Default,
+    /// Restore the settings from before the last power-off.
+    Last,
+}
+
+impl FromStr for PowerOnState {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "DEF" | "DEFAULT" => Ok(Self::Default),
+            "LAST" => Ok(Self::Last),
+            _ => Err(parse_error()),
+        }
+    }
+}
+
+impl std::fmt::Display for PowerOnState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => write!(f, "DEF"),
+            Self::Last => write!(f, "LAST"),
+        }
+    }
+}
+
+/// System language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Language {
+    English,
+    SimplifiedChinese,
+    TraditionalChinese,
+    Japanese,
+    Korean,
+    German,
+    Portuguese,
+    Polish,
+    Russian,
+}
+
+impl FromStr for Language {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "EN" | "ENGLISH" => Ok(Self::English),
+            "CH" | "SCHINESE" => Ok(Self::SimplifiedChinese),
+            "CHT" | "TCHINESE" => Ok(Self::TraditionalChinese),
+            "JAP" | "JAPANESE" => Ok(Self::Japanese),
+            "KOR" | "KOREAN" => Ok(Self::Korean),
+            "GER" | "GERMAN" => Ok(Self::German),
+            "POR" | "PORTUGUESE" => Ok(Self::Portuguese),
+            "POL" | "POLISH" => Ok(Self::Polish),
+            "RUS" | "RUSSIAN" => Ok(Self::Russian),
+            _ => Err(parse_error()),
+        }
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s: &str = match self {
+            Self::English => "EN",
+            Self::SimplifiedChinese => "CH",
+            Self::TraditionalChinese => "CHT",
+            Self::Japanese => "JAP",
+            Self::Korean => "KOR",
+            Self::German => "GER",
+            Self::Portuguese => "POR",
+            Self::Polish => "POL",
+            Self::Russian => "RUS",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Track mode used when channel tracking is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TrackMode {
+    /// Changes to a tracked channel are applied to the tracking channel.
+    Sync,
+    /// Changes are only applied to the channel being modified.
+    Independent,
+}
+
+impl FromStr for TrackMode {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "SYNC" => Ok(Self::Sync),
+            "INDE" | "INDEPENDENT" => Ok(Self::Independent),
+            _ => Err(parse_error()),
+        }
+    }
+}
+
+impl std::fmt::Display for TrackMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sync => write!(f, "SYNC"),
+            Self::Independent => write!(f, "INDE"),
+        }
+    }
+}
+
+/// Entry from the system error queue.
+///
+/// Returned by [`Dp800::error`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemError {
+    /// Error code, `0` if the queue is empty.
+    pub code: i32,
+    /// Error message.
+    pub message: String,
+}
+
+impl SystemError {
+    /// Returns `true` if this entry indicates an empty error queue.
+    pub fn is_none(&self) -> bool {
+        self.code == 0
+    }
+}
+
+impl FromStr for SystemError {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (code, message) = s.split_once(',').ok_or_else(parse_error)?;
+        Ok(Self {
+            code: parse(code.trim())?,
+            message: message.trim().trim_matches('"').to_string(),
+        })
+    }
+}
+
+/// System settings shared by all channels.
+///
+/// Returned by [`Dp800::system_settings`], and applied with
+/// [`Dp800::set_system_settings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemSettings {
+    /// Beeper enabled.
+    pub beeper: bool,
+    /// Display brightness, 1 to 100.
+    pub brightness: u8,
+    /// Display contrast, 1 to 100.
+    pub contrast: u8,
+    /// Screen saver enabled.
+    pub screen_saver: bool,
+    /// Power-on state.
+    pub power_on: PowerOnState,
+    /// Output on/off synchronization enabled.
+    pub onoff_sync: bool,
+    /// System language.
+    pub language: Language,
+    /// Over temperature protection enabled.
+    pub otp: bool,
+    /// Track mode.
+    pub track_mode: TrackMode,
+}
+
+impl Dp800 {
+    /// Returns `true` if the beeper is enabled.
+    pub fn beeper_on(&mut self) -> io::Result<bool> {
+        self.q_bool(":SYST:BEEP?\n")
+    }
+
+    /// Enable or disable the beeper.
+    pub fn set_beeper_on(&mut self, on: bool) -> io::Result<()> {
+        let state: State = on.into();
+        self.cmd(format!(":SYST:BEEP {state}\n").as_str())
+    }
+
+    /// Sound the beeper once.
+    pub fn beep(&mut self) -> io::Result<()> {
+        self.cmd(":SYST:BEEP:IMM\n")
+    }
+
+    /// Display brightness, 1 to 100.
+    pub fn brightness(&mut self) -> io::Result<u8> {
+        self.q_parse(":SYST:BRIG?\n")
+    }
+
+    /// Set the display brightness, 1 to 100.
+    pub fn set_brightness(&mut self, brightness: u8) -> io::Result<()> {
+        self.cmd(format!(":SYST:BRIG {brightness}\n").as_str())
+    }
+
+    /// Display contrast, 1 to 100.
+    pub fn contrast(&mut self) -> io::Result<u8> {
+        self.q_parse(":SYST:CONT?\n")
+    }
+
+    /// Set the display contrast, 1 to 100.
+    pub fn set_contrast(&mut self, contrast: u8) -> io::Result<()> {
+        self.cmd(format!(":SYST:CONT {contrast}\n").as_str())
+    }
+
+    /// Returns `true` if the screen saver is enabled.
+    pub fn screen_saver_on(&mut self) -> io::Result<bool> {
+        self.q_bool(":SYST:SAV?\n")
+    }
+
+    /// Enable or disable the screen saver.
+    pub fn set_screen_saver_on(&mut self, on: bool) -> io::Result<()> {
+        let state: State = on.into();
+        self.cmd(format!(":SYST:SAV {state}\n").as_str())
+    }
+
+    /// Power-on state.
+    pub fn power_on_state(&mut self) -> io::Result<PowerOnState> {
+        self.q_parse(":SYST:POWE?\n")
+    }
+
+    /// Set the power-on state.
+    pub fn set_power_on_state(&mut self, state: PowerOnState) -> io::Result<()> {
+        self.cmd(format!(":SYST:POWE {state}\n").as_str())
+    }
+
+    /// Returns `true` if output on/off synchronization is enabled.
+    pub fn onoff_sync(&mut self) -> io::Result<bool> {
+        self.q_bool(":SYST:ONOFFS?\n")
+    }
+
+    /// Enable or disable output on/off synchronization.
+    pub fn set_onoff_sync(&mut self, on: bool) -> io::Result<()> {
+        let state: State = on.into();
+        self.cmd(format!(":SYST:ONOFFS {state}\n").as_str())
+    }
+
+    /// System language.
+    pub fn language(&mut self) -> io::Result<Language> {
+        self.q_parse(":SYST:LANG:TYPE?\n")
+    }
+
+    /// Set the system language.
+    pub fn set_language(&mut self, language: Language) -> io::Result<()> {
+        self.cmd(format!(":SYST:LANG:TYPE {language}\n").as_str())
+    }
+
+    /// Returns `true` if over temperature protection is enabled.
+    pub fn otp_on(&mut self) -> io::Result<bool> {
+        self.q_bool(":SYST:OTP?\n")
+    }
+
+    /// Enable or disable over temperature protection.
+    pub fn set_otp_on(&mut self, on: bool) -> io::Result<()> {
+        let state: State = on.into();
+        self.cmd(format!(":SYST:OTP {state}\n").as_str())
+    }
+
+    /// Track mode.
+    pub fn track_mode(&mut self) -> io::Result<TrackMode> {
+        self.q_parse(":SYST:TRACKM?\n")
+    }
+
+    /// Set the track mode.
+    pub fn set_track_mode(&mut self, mode: TrackMode) -> io::Result<()> {
+        self.cmd(format!(":SYST:TRACKM {mode}\n").as_str())
+    }
+
+    /// SCPI version the instrument complies with.
+    pub fn scpi_version(&mut self) -> io::Result<String> {
+        self.q(":SYST:VERS?\n")
+    }
+
+    /// Read and remove the oldest entry from the error queue.
+    pub fn error(&mut self) -> io::Result<SystemError> {
+        self.q_parse(":SYST:ERR?\n")
+    }
+
+    /// Switch to remote mode.
+    ///
+    /// The front panel is locked except for the local key.
+    pub fn set_remote(&mut self) -> io::Result<()> {
+        self.cmd(":SYST:REM\n")
+    }
+
+    /// Switch to remote mode and lock the front panel, including the local key.
+    pub fn set_remote_lock(&mut self) -> io::Result<()> {
+        self.cmd(":SYST:RWL\n")
+    }
+
+    /// Return to local mode, unlocking the front panel.
+    pub fn set_local(&mut self) -> io::Result<()> {
+        self.cmd(":SYST:LOC\n")
+    }
+
+    /// Returns `true` if the front panel keys are locked.
+    pub fn key_lock(&mut self) -> io::Result<bool> {
+        self.q_bool(":SYST:KLOC:STAT?\n")
+    }
+
+    /// Lock or unlock the front panel keys.
+    pub fn set_key_lock(&mut self, on: bool) -> io::Result<()> {
+        let state: State = on.into();
+        self.cmd(format!(":SYST:KLOC:STAT {state}\n").as_str())
+    }
+
+    /// Read all system settings.
+    pub fn system_settings(&mut self) -> io::Result<SystemSettings> {
+        Ok(SystemSettings {
+            beeper: self.beeper_on()?,
+            brightness: self.brightness()?,
+            contrast: self.contrast()?,
+            screen_saver: self.screen_saver_on()?,
+            power_on: self.power_on_state()?,
+            onoff_sync: self.onoff_sync()?,
+            language: self.language()?,
+            otp: self.otp_on()?,
+            track_mode: self.track_mode()?,
+        })
+    }
+
+    /// Apply all system settings.
+    pub fn set_system_settings(&mut self, settings: &SystemSettings) -> io::Result<()> {
+        self.set_beeper_on(settings.beeper)?;
+        self.set_brightness(settings.brightness)?;
+        self.set_contrast(settings.contrast)?;
+        self.set_screen_saver_on(settings.screen_saver)?;
+        self.set_power_on_state(settings.power_on)?;
+        self.set_onoff_sync(settings.onoff_sync)?;
+        self.set_language(settings.language)?;
+        self.set_otp_on(settings.otp)?;
+        self.set_track_mode(settings.track_mode)
+    }
+}
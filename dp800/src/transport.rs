@@ -0,0 +1,358 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    net::TcpStream,
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Message transport between [`Dp800`](crate::Dp800) and the instrument.
+pub trait Transport: Send {
+    /// Write a newline terminated message.
+    fn write(&mut self, msg: &str) -> io::Result<()>;
+
+    /// Read a response, without the newline terminator.
+    ///
+    /// Returns [`io::ErrorKind::UnexpectedEof`] if the connection was closed.
+    fn read_line(&mut self) -> io::Result<String>;
+
+    /// Discard any pending input, returning the number of bytes discarded.
+    fn drain(&mut self) -> io::Result<usize>;
+}
+
+/// Telnet transport over TCP.
+///
+/// Created by [`Dp800Builder::connect_tcp`](crate::Dp800Builder::connect_tcp).
+pub struct TcpTransport {
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+}
+
+impl TcpTransport {
+    /// Create a transport from a connected stream.
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: BufWriter::new(stream),
+        })
+    }
+}
+
+impl Transport for TcpTransport {
+    fn write(&mut self, msg: &str) -> io::Result<()> {
+        self.writer.write_all(msg.as_bytes())?;
+        self.writer.flush()
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut buf: String = String::with_capacity(64);
+        if self.reader.read_line(&mut buf)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buf.pop();
+        Ok(buf)
+    }
+
+    fn drain(&mut self) -> io::Result<usize> {
+        let mut discarded: usize = self.reader.buffer().len();
+        self.reader.consume(discarded);
+
+        let stream: &mut TcpStream = self.reader.get_mut();
+        stream.set_nonblocking(true)?;
+        let mut buf: [u8; 256] = [0; 256];
+        let result: io::Result<usize> = loop {
+            match stream.read(&mut buf) {
+                Ok(0) => break Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => discarded += n,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break Ok(discarded),
+                Err(e) => break Err(e),
+            }
+        };
+        stream.set_nonblocking(false)?;
+        result
+    }
+}
+
+const SENT_PREFIX: &str = "> ";
+const RECEIVED_PREFIX: &str = "< ";
+
+/// Transport that records a transcript of another transport.
+///
+/// The transcript has one message per line, sent messages are prefixed
+/// with `> ` and received responses with `< `, for example:
+///
+/// ```text
+/// > *IDN?
+/// < RIGOL TECHNOLOGIES,DP832,DP8C000000000,00.01.16
+/// > :SOUR1:VOLT 3.300
+/// ```
+///
+/// Transcripts can be played back with [`Replay`].
+pub struct Recorder<T, W> {
+    inner: T,
+    transcript: W,
+}
+
+impl<T: Transport> Recorder<T, BufWriter<File>> {
+    /// Record a transcript to a file, replacing the file if it exists.
+    pub fn create<P: AsRef<Path>>(inner: T, path: P) -> io::Result<Self> {
+        Ok(Self::new(inner, BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<T: Transport, W: Write + Send> Recorder<T, W> {
+    /// Record a transcript to a writer.
+    pub fn new(inner: T, transcript: W) -> Self {
+        Self { inner, transcript }
+    }
+
+    fn record(&mut self, prefix: &str, msg: &str) -> io::Result<()> {
+        writeln!(self.transcript, "{prefix}{msg}")?;
+        self.transcript.flush()
+    }
+}
+
+impl<T: Transport, W: Write + Send> Transport for Recorder<T, W> {
+    fn write(&mut self, msg: &str) -> io::Result<()> {
+        self.inner.write(msg)?;
+        self.record(SENT_PREFIX, msg.trim_end())
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let response: String = self.inner.read_line()?;
+        self.record(RECEIVED_PREFIX, response.as_str())?;
+        Ok(response)
+    }
+
+    fn drain(&mut self) -> io::Result<usize> {
+        self.inner.drain()
+    }
+}
+
+enum Entry {
+    Sent(String),
+    Received(String),
+}
+
+/// Transport that plays back a transcript recorded with [`Recorder`].
+///
+/// Every message sent must match the next message in the transcript,
+/// otherwise an [`io::ErrorKind::InvalidData`] error is returned.
+/// Reading a response that is not next in the transcript returns an
+/// [`io::ErrorKind::TimedOut`] error, as a real instrument would.
+///
+/// Clones share the same transcript position, keep a clone to call
+/// [`Replay::finish`] after passing the transport to
+/// [`Dp800Builder::build`](crate::Dp800Builder::build).
+///
+/// Empty lines and lines beginning with `#` in the transcript are ignored.
+#[derive(Clone)]
+pub struct Replay {
+    entries: Arc<Mutex<VecDeque<Entry>>>,
+}
+
+impl std::str::FromStr for Replay {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_reader(s.as_bytes())
+    }
+}
+
+impl Replay {
+    /// Load a transcript from a file.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Load a transcript from a reader.
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut entries: VecDeque<Entry> = VecDeque::new();
+        for (idx, line) in reader.lines().enumerate() {
+            let line: String = line?;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry: Entry = if let Some(msg) = line.strip_prefix(SENT_PREFIX) {
+                Entry::Sent(msg.to_string())
+            } else if let Some(response) = line.strip_prefix(RECEIVED_PREFIX) {
+                Entry::Received(response.to_string())
+            } else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid transcript entry on line {}: {line}", idx + 1),
+                ));
+            };
+            entries.push_back(entry);
+        }
+        Ok(Self {
+            entries: Arc::new(Mutex::new(entries)),
+        })
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, VecDeque<Entry>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Number of transcript entries that have not been played back.
+    pub fn remaining(&self) -> usize {
+        self.entries().len()
+    }
+
+    /// Returns an error if the transcript has not been played back entirely.
+    pub fn finish(&self) -> io::Result<()> {
+        match self.entries().front() {
+            None => Ok(()),
+            Some(Entry::Sent(msg)) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Expected command was not sent: {msg}"),
+            )),
+            Some(Entry::Received(response)) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Response was not read: {response}"),
+            )),
+        }
+    }
+}
+
+impl Transport for Replay {
+    fn write(&mut self, msg: &str) -> io::Result<()> {
+        let msg: &str = msg.trim_end();
+        let mut entries = self.entries();
+        match entries.front() {
+            Some(Entry::Sent(expected)) if expected == msg => {
+                entries.pop_front();
+                Ok(())
+            }
+            Some(Entry::Sent(expected)) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unexpected command: {msg}, expected: {expected}"),
+            )),
+            Some(Entry::Received(_)) | None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unexpected command: {msg}"),
+            )),
+        }
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut entries = self.entries();
+        match entries.pop_front() {
+            Some(Entry::Received(response)) => Ok(response),
+            Some(entry) => {
+                entries.push_front(entry);
+                Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "No response in transcript",
+                ))
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "No response in transcript",
+            )),
+        }
+    }
+
+    fn drain(&mut self) -> io::Result<usize> {
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Amps, Dp800, Dp800Builder, Volts};
+
+    const IDN: &str = "RIGOL TECHNOLOGIES,DP832,DP8C000000000,00.01.16";
+
+    fn build(transcript: &str) -> (Dp800, Replay) {
+        let replay: Replay = transcript.parse().unwrap();
+        (Dp800Builder::new().build(replay.clone()), replay)
+    }
+
+    #[test]
+    fn replay_typed_calls() {
+        let (mut dp800, replay) = build(&format!(
+            "# comment\n\
+             > *IDN?\n\
+             < {IDN}\n\
+             \n\
+             > :SOUR1:VOLT 3.30\n\
+             > :OUTP CH1,ON\n\
+             > :OUTP? CH1\n\
+             < ON\n\
+             > :SOUR1:VOLT?\n\
+             < 3.300\n"
+        ));
+        assert_eq!(dp800.set_voltage(1, 3.3).unwrap(), Volts(3.3));
+        dp800.set_output_state(1, true).unwrap();
+        assert!(dp800.output_state(1).unwrap());
+        assert_eq!(replay.remaining(), 2);
+        assert_eq!(dp800.volts(1).unwrap(), Volts(3.3));
+        replay.finish().unwrap();
+    }
+
+    #[test]
+    fn replay_unsupported_model() {
+        let (mut dp800, replay) = build(
+            "> *IDN?\n\
+             < RIGOL TECHNOLOGIES,DP899,DP9C000000000,00.01.16\n\
+             > :SOUR1:VOLT 3.300\n\
+             > :SOUR1:CURR 0.123\n",
+        );
+        assert_eq!(dp800.set_voltage(1, 3.3).unwrap(), Volts(3.3));
+        assert_eq!(dp800.set_current(1, 0.12345).unwrap(), Amps(0.123));
+        replay.finish().unwrap();
+    }
+
+    #[test]
+    fn replay_wrong_command() {
+        let (mut dp800, replay) = build("> :OUTP CH1,ON\n");
+        let err: io::Error = dp800.set_output_state(2, true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(replay.remaining(), 1);
+    }
+
+    #[test]
+    fn replay_missing_response() {
+        let (mut dp800, _) = build("> :OUTP? CH1\n");
+        let err: io::Error = dp800.output_state(1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn replay_finish() {
+        let mut replay: Replay = "> :OUTP? CH1\n< OFF\n".parse().unwrap();
+        let err: io::Error = replay.finish().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        replay.write(":OUTP? CH1\n").unwrap();
+        let err: io::Error = replay.finish().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        assert_eq!(replay.read_line().unwrap(), "OFF");
+        replay.finish().unwrap();
+    }
+
+    #[test]
+    fn transcript_invalid_entry() {
+        let err: io::Error = "> *IDN?\nRIGOL\n".parse::<Replay>().err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn recorder_transcript() {
+        let replay: Replay = format!("> *IDN?\n< {IDN}\n").parse().unwrap();
+        let mut transcript: Vec<u8> = Vec::new();
+        let mut recorder = Recorder::new(replay, &mut transcript);
+        recorder.write("*IDN?\n").unwrap();
+        assert_eq!(recorder.read_line().unwrap(), IDN);
+        drop(recorder);
+        assert_eq!(
+            String::from_utf8(transcript).unwrap(),
+            format!("> *IDN?\n< {IDN}\n")
+        );
+    }
+}
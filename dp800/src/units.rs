@@ -0,0 +1,193 @@
+use crate::{ChannelState, Dp800, Measurement, parse_error};
+use std::{fmt, io, str::FromStr};
+
+/// Parse a quantity with an optional SI prefix and unit, for example
+/// `"3.3V"`, `"250mA"`, or `"1.5"`.
+///
+/// Infinite and NaN values are rejected.
+fn parse_quantity(s: &str, unit: char) -> io::Result<f32> {
+    let s: &str = s.trim();
+    let s: &str = s
+        .strip_suffix(unit)
+        .or_else(|| s.strip_suffix(unit.to_ascii_lowercase()))
+        .unwrap_or(s)
+        .trim_end();
+    let (number, scale): (&str, f64) = if let Some(number) = s.strip_suffix('m') {
+        (number, 1e-3)
+    } else if let Some(number) = s.strip_suffix(['u', 'µ']) {
+        (number, 1e-6)
+    } else if let Some(number) = s.strip_suffix('k') {
+        (number, 1e3)
+    } else {
+        (s, 1.0)
+    };
+    let value: f64 = number.trim_end().parse().map_err(|_| parse_error())?;
+    let value: f32 = (value * scale) as f32;
+    if !value.is_finite() {
+        return Err(parse_error());
+    }
+    Ok(value)
+}
+
+macro_rules! quantity {
+    ($(#[$meta:meta])* $name:ident, $unit:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(transparent))]
+        pub struct $name(pub f32);
+
+        impl From<f32> for $name {
+            fn from(value: f32) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for f32 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = io::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                parse_quantity(s, $unit).map(Self)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)?;
+                f.write_str(concat!($unit))
+            }
+        }
+    };
+}
+
+quantity!(
+    /// Voltage in volts.
+    ///
+    /// Parses from strings such as `"3.3V"`, `"500mV"`, or `"12"`.
+    Volts,
+    'V'
+);
+
+quantity!(
+    /// Current in amps.
+    ///
+    /// Parses from strings such as `"1.5A"`, `"250mA"`, or `"0.1"`.
+    Amps,
+    'A'
+);
+
+quantity!(
+    /// Power in watts.
+    ///
+    /// Parses from strings such as `"10W"`, `"250mW"`, or `"1.5"`.
+    Watts,
+    'W'
+);
+
+impl Dp800 {
+    /// Setpoint voltage.
+    pub fn volts(&mut self, ch: u8) -> io::Result<Volts> {
+        self.voltage(ch).map(Volts)
+    }
+
+    /// Setpoint current.
+    pub fn amps(&mut self, ch: u8) -> io::Result<Amps> {
+        self.current(ch).map(Amps)
+    }
+
+    /// Over voltage protection value.
+    pub fn ovp_volts(&mut self, ch: u8) -> io::Result<Volts> {
+        self.ovp(ch).map(Volts)
+    }
+
+    /// Over current protection value.
+    pub fn ocp_amps(&mut self, ch: u8) -> io::Result<Amps> {
+        self.ocp(ch).map(Amps)
+    }
+
+    /// Measure the voltage.
+    pub fn measure_volts(&mut self, ch: u8) -> io::Result<Volts> {
+        self.measure_voltage(ch).map(Volts)
+    }
+
+    /// Measure the current.
+    pub fn measure_amps(&mut self, ch: u8) -> io::Result<Amps> {
+        self.measure_current(ch).map(Amps)
+    }
+
+    /// Measure the power.
+    pub fn measure_watts(&mut self, ch: u8) -> io::Result<Watts> {
+        self.measure_power(ch).map(Watts)
+    }
+}
+
+impl ChannelState {
+    /// Setpoint voltage.
+    pub fn volts(&self) -> Volts {
+        Volts(self.voltage)
+    }
+
+    /// Setpoint current.
+    pub fn amps(&self) -> Amps {
+        Amps(self.current)
+    }
+
+    /// Over voltage protection value.
+    pub fn ovp_volts(&self) -> Volts {
+        Volts(self.ovp)
+    }
+
+    /// Over current protection value.
+    pub fn ocp_amps(&self) -> Amps {
+        Amps(self.ocp)
+    }
+}
+
+impl Measurement {
+    /// Measured voltage.
+    pub fn volts(&self) -> Volts {
+        Volts(self.voltage)
+    }
+
+    /// Measured current.
+    pub fn amps(&self) -> Amps {
+        Amps(self.current)
+    }
+
+    /// Measured power.
+    pub fn watts(&self) -> Watts {
+        Watts(self.power)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!("3.3V".parse::<Volts>().unwrap(), Volts(3.3));
+        assert_eq!("3.3 V".parse::<Volts>().unwrap(), Volts(3.3));
+        assert_eq!("3.3v".parse::<Volts>().unwrap(), Volts(3.3));
+        assert_eq!("500mV".parse::<Volts>().unwrap(), Volts(0.5));
+        assert_eq!("1.5".parse::<Volts>().unwrap(), Volts(1.5));
+        assert_eq!("250mA".parse::<Amps>().unwrap(), Amps(0.25));
+        assert_eq!("100uA".parse::<Amps>().unwrap(), Amps(0.0001));
+        assert_eq!("1.5kW".parse::<Watts>().unwrap(), Watts(1500.0));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for s in [
+            "", "V", "3.3A", "3.3 mm", "x", "inf", "-inf", "NaN", "infV", "1e39V",
+        ] {
+            assert!(s.parse::<Volts>().is_err(), "{s}");
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use crate::{Amps, Dp800, Volts};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// Setpoints of a channel at a point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point {
+    /// Time from the start of the waveform.
+    pub time: Duration,
+    /// Channel, 1-indexed.
+    pub channel: u8,
+    /// Voltage setpoint in Volts.
+    pub voltage: f32,
+    /// Current setpoint in Amps.
+    pub current: f32,
+}
+
+/// Setpoint waveform for [`Dp800::play`].
+///
+/// Waveforms are loaded from CSV with one point per row, and columns for
+/// the time in seconds, channel, voltage, and current:
+///
+/// ```text
+/// time,channel,volts,amps
+/// 0.0,1,0.0,0.5
+/// 0.5,1,3.3,0.5
+/// 0.5,2,1.8V,100mA
+/// 1.0,1,5.0,0.5
+/// ```
+///
+/// The header row is optional, empty lines and lines beginning with `#`
+/// are ignored.
+/// Points are played in order of time, points with the same time are
+/// played in the order they appear.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Waveform {
+    points: Vec<Point>,
+}
+
+fn invalid_row(line: usize, msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid waveform row on line {line}: {msg}"),
+    )
+}
+
+fn parse_point(line: usize, row: &str) -> io::Result<Point> {
+    let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+    let [time, channel, voltage, current] = fields.as_slice() else {
+        return Err(invalid_row(line, "expected 4 columns"));
+    };
+    let time: Duration = time
+        .parse::<f64>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| invalid_row(line, "invalid time"))?;
+    let channel: u8 = channel
+        .parse()
+        .map_err(|_| invalid_row(line, "invalid channel"))?;
+    let Volts(voltage) = voltage
+        .parse()
+        .map_err(|_| invalid_row(line, "invalid voltage"))?;
+    let Amps(current) = current
+        .parse()
+        .map_err(|_| invalid_row(line, "invalid current"))?;
+    Ok(Point {
+        time,
+        channel,
+        voltage,
+        current,
+    })
+}
+
+impl FromStr for Waveform {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_reader(s.as_bytes())
+    }
+}
+
+impl Waveform {
+    /// Load a waveform from a CSV file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Load a waveform from a CSV reader.
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut points: Vec<Point> = Vec::new();
+        let mut first: bool = true;
+        for (idx, line) in reader.lines().enumerate() {
+            let line: String = line?;
+            let row: &str = line.trim();
+            if row.is_empty() || row.starts_with('#') {
+                continue;
+            }
+            let is_header: bool = first && row.starts_with(|c: char| c.is_ascii_alphabetic());
+            first = false;
+            if !is_header {
+                points.push(parse_point(idx + 1, row)?);
+            }
+        }
+        Ok(Self::from_points(points))
+    }
+
+    /// Create a waveform from points.
+    pub fn from_points(mut points: Vec<Point>) -> Self {
+        points.sort_by_key(|point| point.time);
+        Self { points }
+    }
+
+    /// Points in the order they are played.
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// Duration from the start of the waveform to the last point.
+    pub fn duration(&self) -> Duration {
+        self.points
+            .last()
+            .map_or(Duration::ZERO, |point| point.time)
+    }
+}
+
+/// Scheduled and achieved time of a point played by [`Dp800::play`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    /// Time the point was scheduled for.
+    pub scheduled: Duration,
+    /// Time the setpoints of the point were sent.
+    pub achieved: Duration,
+}
+
+impl Timing {
+    /// Difference between the achieved and scheduled time.
+    pub fn jitter(&self) -> Duration {
+        self.achieved.saturating_sub(self.scheduled)
+    }
+}
+
+/// Timing of a waveform played by [`Dp800::play`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlaybackReport {
+    /// Timing of every point, in the order played.
+    pub timings: Vec<Timing>,
+}
+
+impl PlaybackReport {
+    /// Mean difference between the achieved and scheduled times.
+    pub fn mean_jitter(&self) -> Duration {
+        if self.timings.is_empty() {
+            return Duration::ZERO;
+        }
+        let total: Duration = self.timings.iter().map(Timing::jitter).sum();
+        total.div_f64(self.timings.len() as f64)
+    }
+
+    /// Maximum difference between the achieved and scheduled times.
+    pub fn max_jitter(&self) -> Duration {
+        self.timings
+            .iter()
+            .map(Timing::jitter)
+            .max()
+            .unwrap_or(Duration::ZERO)
+    }
+}
+
+impl Dp800 {
+    /// Play a waveform, blocking until the last point has been sent.
+    ///
+    /// Points are scheduled relative to the start of playback, so delays in
+    /// sending one point do not accumulate into later points.
+    /// Points that are late are sent immediately.
+    ///
+    /// Returns an [`io::ErrorKind::InvalidInput`] error before playback if a
+    /// point is on a channel the model does not have.
+    /// Channels are not checked for unsupported models.
+    pub fn play(&mut self, waveform: &Waveform) -> io::Result<PlaybackReport> {
+        // the model is required for setpoint resolution, read it before
+        // playback starts so the first point is not delayed
+        match self.model() {
+            Ok(model) => {
+                let num_channels: u8 = model.num_channels();
+                if let Some(point) = waveform
+                    .points
+                    .iter()
+                    .find(|point| !(1..=num_channels).contains(&point.channel))
+                {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "Waveform channel {} does not exist, instrument has {num_channels} channels",
+                            point.channel
+                        ),
+                    ));
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::Unsupported => (),
+            Err(e) => return Err(e),
+        }
+
+        let mut timings: Vec<Timing> = Vec::with_capacity(waveform.points.len());
+        let start: Instant = Instant::now();
+        for point in waveform.points.iter() {
+            if let Some(remaining) = point.time.checked_sub(start.elapsed()) {
+                std::thread::sleep(remaining);
+            }
+            self.set_voltage(point.channel, point.voltage)?;
+            self.set_current(point.channel, point.current)?;
+            timings.push(Timing {
+                scheduled: point.time,
+                achieved: start.elapsed(),
+            });
+        }
+        Ok(PlaybackReport { timings })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dp800Builder, Replay};
+
+    #[test]
+    fn parse() {
+        let waveform: Waveform = "time,channel,volts,amps\n\
+                                  # comment\n\
+                                  \n\
+                                  1.0,1,5.0,0.5\n\
+                                  0.5, 2, 1.8V, 100mA\n\
+                                  0.5,1,3.3,0.5\n"
+            .parse()
+            .unwrap();
+        let point = |ms: u64, channel: u8, voltage: f32, current: f32| Point {
+            time: Duration::from_millis(ms),
+            channel,
+            voltage,
+            current,
+        };
+        assert_eq!(
+            waveform.points(),
+            [
+                point(500, 2, 1.8, 0.1),
+                point(500, 1, 3.3, 0.5),
+                point(1000, 1, 5.0, 0.5),
+            ]
+        );
+        assert_eq!(waveform.duration(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn parse_without_header() {
+        let waveform: Waveform = "0,1,0,0.5\n".parse().unwrap();
+        assert_eq!(waveform.points().len(), 1);
+    }
+
+    #[test]
+    fn parse_errors() {
+        for (csv, msg) in [
+            ("0,1,0\n", "line 1: expected 4 columns"),
+            ("0,1,0,0.5\n-1,1,0,0.5\n", "line 2: invalid time"),
+            (
+                "# comment\nx,1,0,0.5\n0,x,0,0.5\n",
+                "line 3: invalid channel",
+            ),
+            ("0,1,3.3A,0.5\n", "line 1: invalid voltage"),
+            ("0,1,3.3,inf\n", "line 1: invalid current"),
+        ] {
+            let err: io::Error = csv.parse::<Waveform>().unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert!(err.to_string().ends_with(msg), "{err}");
+        }
+    }
+
+    fn play(idn: &str, transcript: &str, csv: &str) -> io::Result<PlaybackReport> {
+        let replay: Replay = format!(
+            "> *IDN?\n\
+             < RIGOL TECHNOLOGIES,{idn},DP8C000000000,00.01.16\n\
+             {transcript}"
+        )
+        .parse()
+        .unwrap();
+        let mut dp800: Dp800 = Dp800Builder::new().build(replay.clone());
+        let result: io::Result<PlaybackReport> = dp800.play(&csv.parse().unwrap());
+        replay.finish().unwrap();
+        result
+    }
+
+    #[test]
+    fn play_invalid_channel() {
+        let err: io::Error = play("DP832", "", "0,1,0,0.5\n0,4,0,0.5\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn play_unsupported_model() {
+        let report: PlaybackReport = play(
+            "DP899",
+            "> :SOUR1:VOLT 3.300\n> :SOUR1:CURR 0.500\n",
+            "0,1,3.3,0.5\n",
+        )
+        .unwrap();
+        assert_eq!(report.timings.len(), 1);
+    }
+}
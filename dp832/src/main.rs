@@ -4,7 +4,7 @@ use crossterm::{
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use dp800::{Dp800, Measurement};
+use dp800::{ChannelState, ConnectionEvent, Dp800Builder, Measurement, Reconnecting, Status};
 use ratatui::{
     Frame, Terminal,
     backend::{Backend, CrosstermBackend},
@@ -19,6 +19,8 @@ use std::{
     time::{Duration, Instant},
 };
 
+mod trace;
+
 const NUM_CH: usize = 3;
 
 /// Vertical selection
@@ -76,20 +78,12 @@ impl Vsel {
 
 #[derive(Default)]
 struct Data {
-    output_state: bool,
-    meas_voltage: f32,
-    meas_current: f32,
-    meas_power: f32,
-    sp_voltage: f32,
-    sp_current: f32,
-    limit_voltage: f32,
-    limit_current: f32,
-    ovp_on: bool,
-    ocp_on: bool,
+    state: ChannelState,
+    meas: Measurement,
 }
 
 struct App {
-    dp832: Dp800,
+    dp832: Reconnecting,
     data: [Data; NUM_CH],
     ch: u8,
     vsel: Vsel,
@@ -99,22 +93,15 @@ struct App {
 
 impl App {
     fn on_tick(&mut self) -> anyhow::Result<()> {
-        for (idx, data) in self.data.iter_mut().enumerate() {
-            let ch_idx = u8::try_from(idx).unwrap() + 1;
-            let meas: Measurement = self.dp832.measure(ch_idx)?;
-
-            *data = Data {
-                output_state: self.dp832.output_state(ch_idx)?,
-                meas_voltage: meas.voltage,
-                meas_current: meas.current,
-                meas_power: meas.power,
-                sp_voltage: self.dp832.voltage(ch_idx)?,
-                sp_current: self.dp832.current(ch_idx)?,
-                limit_voltage: self.dp832.ovp(ch_idx)?,
-                limit_current: self.dp832.ocp(ch_idx)?,
-                ovp_on: self.dp832.ovp_on(ch_idx)?,
-                ocp_on: self.dp832.ocp_on(ch_idx)?,
-            };
+        let status: Status = self.dp832.retry(|dp| dp.status())?;
+
+        for ((data, state), meas) in self
+            .data
+            .iter_mut()
+            .zip(status.state.channels)
+            .zip(status.measurements)
+        {
+            *data = Data { state, meas };
         }
 
         Ok(())
@@ -127,7 +114,7 @@ impl App {
 
 fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
-    mut app: App,
+    app: &mut App,
     tick_rate: Duration,
 ) -> anyhow::Result<()> {
     app.on_tick()?;
@@ -135,7 +122,7 @@ fn run_app<B: Backend>(
     let mut last_tick: Instant = Instant::now();
     loop {
         terminal
-            .draw(|f| ui(f, &app))
+            .draw(|f| ui(f, app))
             .ok()
             .context("Failed to draw")?;
 
@@ -156,11 +143,20 @@ fn run_app<B: Backend>(
                         // a simple weekend project
                         let value: f32 = app.input.parse().unwrap();
                         app.input = String::new();
+                        let ch: u8 = app.ch;
                         match app.vsel {
-                            Vsel::SetVolt => app.dp832.set_voltage(app.ch, value)?,
-                            Vsel::SetAmp => app.dp832.set_current(app.ch, value)?,
-                            Vsel::Ovp => app.dp832.set_ovp(app.ch, value)?,
-                            Vsel::Ocp => app.dp832.set_ocp(app.ch, value)?,
+                            Vsel::SetVolt => {
+                                app.dp832.retry(|dp| dp.set_voltage(ch, value))?;
+                            }
+                            Vsel::SetAmp => {
+                                app.dp832.retry(|dp| dp.set_current(ch, value))?;
+                            }
+                            Vsel::Ovp => {
+                                app.dp832.retry(|dp| dp.set_ovp(ch, value))?;
+                            }
+                            Vsel::Ocp => {
+                                app.dp832.retry(|dp| dp.set_ocp(ch, value))?;
+                            }
                             Vsel::Measure | Vsel::OvpOn | Vsel::OcpOn => unreachable!(),
                         }
                     }
@@ -185,20 +181,16 @@ fn run_app<B: Backend>(
                         if usize::from(app.ch) > NUM_CH {
                             app.ch = 1;
                         }
-                        app.dp832.set_ch(app.ch)?;
-                        // switching channels too quickly can cause the PSU
-                        // to report invalid commands
-                        std::thread::sleep(Duration::from_millis(50));
+                        let ch: u8 = app.ch;
+                        app.dp832.retry(|dp| dp.set_ch(ch))?;
                     }
                     KeyCode::Left | KeyCode::Char('h') => {
                         app.ch -= 1;
                         if app.ch == 0 {
                             app.ch = NUM_CH as u8;
                         }
-                        app.dp832.set_ch(app.ch)?;
-                        // switching channels too quickly can cause the PSU
-                        // to report invalid commands
-                        std::thread::sleep(Duration::from_millis(50));
+                        let ch: u8 = app.ch;
+                        app.dp832.retry(|dp| dp.set_ch(ch))?;
                     }
                     KeyCode::Up | KeyCode::Char('k') => {
                         app.vsel = app.vsel.prev();
@@ -207,15 +199,22 @@ fn run_app<B: Backend>(
                         app.vsel = app.vsel.next();
                     }
                     KeyCode::Enter => match app.vsel {
-                        Vsel::Measure => app
-                            .dp832
-                            .set_output_state(app.ch, !app.ch_data().output_state)?,
+                        Vsel::Measure => {
+                            let (ch, on) = (app.ch, !app.ch_data().state.output);
+                            app.dp832.retry(|dp| dp.set_output_state(ch, on))?
+                        }
                         Vsel::SetVolt => app.input_title = "Voltage Setpoint (V)".to_string(),
                         Vsel::SetAmp => app.input_title = "Current Setpoint (A)".to_string(),
                         Vsel::Ovp => app.input_title = "Over Voltage Protection (V)".to_string(),
                         Vsel::Ocp => app.input_title = "Over Current Protection (A)".to_string(),
-                        Vsel::OvpOn => app.dp832.set_ovp_on(app.ch, !app.ch_data().ovp_on)?,
-                        Vsel::OcpOn => app.dp832.set_ocp_on(app.ch, !app.ch_data().ocp_on)?,
+                        Vsel::OvpOn => {
+                            let (ch, on) = (app.ch, !app.ch_data().state.ovp_on);
+                            app.dp832.retry(|dp| dp.set_ovp_on(ch, on))?
+                        }
+                        Vsel::OcpOn => {
+                            let (ch, on) = (app.ch, !app.ch_data().state.ocp_on);
+                            app.dp832.retry(|dp| dp.set_ocp_on(ch, on))?
+                        }
                     },
                     _ => {}
                 }
@@ -264,7 +263,7 @@ fn ui(f: &mut Frame, app: &App) {
         let ch_selected: bool = ch_idx == app.ch;
 
         let title_style: Style = {
-            let title_color: Color = if data.output_state {
+            let title_color: Color = if data.state.output {
                 Color::Green
             } else {
                 Color::White
@@ -289,7 +288,7 @@ fn ui(f: &mut Frame, app: &App) {
         {
             let selected: bool = ch_selected && matches!(app.vsel, Vsel::Measure);
             let block_color: Color = block_color(selected);
-            let power_state: &str = bool_to_on_off(data.output_state);
+            let power_state: &str = bool_to_on_off(data.state.output);
 
             let title: String = format!("CH{ch_idx} - {power_state}");
 
@@ -299,7 +298,7 @@ fn ui(f: &mut Frame, app: &App) {
                 .title(Span::styled(title, title_style));
 
             let mut style: Style = Style::default().add_modifier(Modifier::BOLD);
-            if !data.output_state {
+            if !data.state.output {
                 style = style.add_modifier(Modifier::DIM)
             } else {
                 style = style.fg(Color::White)
@@ -308,7 +307,7 @@ fn ui(f: &mut Frame, app: &App) {
             let paragraph: Paragraph = Paragraph::new(Text::styled(
                 format!(
                     "{:>6.3} V\n{:>6.3} A\n{:>6.3} W",
-                    data.meas_voltage, data.meas_current, data.meas_power
+                    data.meas.voltage, data.meas.current, data.meas.power
                 ),
                 style,
             ))
@@ -327,8 +326,8 @@ fn ui(f: &mut Frame, app: &App) {
                 .title(Span::styled("Set", title_style));
 
             let list_items: [ListItem; 2] = [
-                ListItem::new(format!("{:>6.3} V", data.sp_voltage)),
-                ListItem::new(format!("{:>6.3} A", data.sp_current)),
+                ListItem::new(format!("{:>6.3} V", data.state.voltage)),
+                ListItem::new(format!("{:>6.3} A", data.state.current)),
             ];
 
             let list: List = List::new(list_items)
@@ -355,19 +354,19 @@ fn ui(f: &mut Frame, app: &App) {
                 .title(Span::styled("Limit", title_style));
 
             let mut ocp_style: Style = Style::default();
-            if !data.ocp_on {
+            if !data.state.ocp_on {
                 ocp_style = ocp_style.add_modifier(Modifier::DIM)
             }
             let mut ovp_style: Style = Style::default();
-            if !data.ovp_on {
+            if !data.state.ovp_on {
                 ovp_style = ovp_style.add_modifier(Modifier::DIM)
             }
 
             let list_items: [ListItem; 4] = [
-                ListItem::new(format!("{:>6.3} V", data.limit_voltage)).style(ovp_style),
-                ListItem::new(format!("{:>6.3} A", data.limit_current)).style(ocp_style),
-                ListItem::new(format!("OVP: {}", bool_to_on_off(data.ovp_on))),
-                ListItem::new(format!("OCP: {}", bool_to_on_off(data.ocp_on))),
+                ListItem::new(format!("{:>6.3} V", data.state.ovp)).style(ovp_style),
+                ListItem::new(format!("{:>6.3} A", data.state.ocp)).style(ocp_style),
+                ListItem::new(format!("OVP: {}", bool_to_on_off(data.state.ovp_on))),
+                ListItem::new(format!("OCP: {}", bool_to_on_off(data.state.ocp_on))),
             ];
 
             let list: List = List::new(list_items)
@@ -406,22 +405,43 @@ fn ui(f: &mut Frame, app: &App) {
     }
 }
 
-fn main() -> anyhow::Result<()> {
-    let mut conf: PathBuf =
-        dirs::config_dir().context("Unable to locate configuration directory")?;
-    conf.push("dp832.txt");
-
-    let conf_file_contents: String = std::fs::read_to_string(&conf)
-        .with_context(|| format!("Failed to read configuration file {}", conf.display()))?;
-
-    let address: &str = conf_file_contents.trim();
+/// Command line arguments.
+#[derive(Default)]
+struct Args {
+    /// Lock the front panel while running.
+    lock: bool,
+    /// Write the SCPI transcript to this file.
+    trace_file: Option<PathBuf>,
+}
 
-    log::debug!("Connecting to {address}");
-    let mut dp832: Dp800 = Dp800::connect(address)
-        .with_context(|| format!("Failed to connect to power supply at {address}"))?;
-    log::debug!("Connected");
-    let ch: u8 = dp832.ch()?;
+impl Args {
+    fn parse() -> anyhow::Result<Self> {
+        let mut args: Args = Args::default();
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--lock" => args.lock = true,
+                "--trace-file" => {
+                    let path: String = iter.next().context("--trace-file requires a path")?;
+                    args.trace_file = Some(path.into());
+                }
+                "-h" | "--help" => {
+                    println!("Usage: dp832 [--lock] [--trace-file <path>]");
+                    println!();
+                    println!("Options:");
+                    println!("  --lock               Lock the front panel while running");
+                    println!("  --trace-file <path>  Write the SCPI transcript to a file");
+                    std::process::exit(0);
+                }
+                _ => anyhow::bail!("Unknown argument: {arg}"),
+            }
+        }
+        Ok(args)
+    }
+}
 
+/// Set up the terminal, run the app, and restore the terminal.
+fn run_terminal(app: &mut App) -> anyhow::Result<()> {
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -431,14 +451,6 @@ fn main() -> anyhow::Result<()> {
 
     // create app and run it
     const TICK_RATE: Duration = Duration::from_millis(250);
-    let app = App {
-        dp832,
-        ch,
-        vsel: Vsel::Measure,
-        input_title: String::new(),
-        input: String::new(),
-        data: Default::default(),
-    };
     let res = run_app(&mut terminal, app, TICK_RATE);
 
     // restore terminal
@@ -451,6 +463,66 @@ fn main() -> anyhow::Result<()> {
     terminal.show_cursor()?;
 
     res?;
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    let args: Args = Args::parse()?;
+
+    if let Some(path) = args.trace_file.as_ref() {
+        trace::init(path)
+            .with_context(|| format!("Failed to create trace file {}", path.display()))?;
+    }
+
+    let mut conf: PathBuf =
+        dirs::config_dir().context("Unable to locate configuration directory")?;
+    conf.push("dp832.txt");
+
+    let conf_file_contents: String = std::fs::read_to_string(&conf)
+        .with_context(|| format!("Failed to read configuration file {}", conf.display()))?;
+
+    let address: &str = conf_file_contents.trim();
+
+    log::debug!("Connecting to {address}");
+    // switching channels too quickly can cause the PSU to report invalid
+    // commands
+    let builder: Dp800Builder = Dp800Builder::new()
+        .connect_timeout(Duration::from_secs(5))
+        .command_delay(Duration::from_millis(50));
+    let mut dp832: Reconnecting = Reconnecting::connect_with(builder, address)
+        .with_context(|| format!("Failed to connect to power supply at {address}"))?
+        .on_event(|event| match event {
+            ConnectionEvent::Lost(e) => log::warn!("Connection lost: {e}"),
+            ConnectionEvent::ReconnectFailed { attempt, error } => {
+                log::warn!("Reconnection attempt {attempt} failed: {error}")
+            }
+            ConnectionEvent::Restored => log::info!("Connection restored"),
+        });
+    log::debug!("Connected");
+    let ch: u8 = dp832.retry(|dp| dp.ch())?;
+    if args.lock {
+        dp832.retry(|dp| dp.set_remote_lock())?;
+    }
+
+    let mut app = App {
+        dp832,
+        ch,
+        vsel: Vsel::Measure,
+        input_title: String::new(),
+        input: String::new(),
+        data: Default::default(),
+    };
+    let res = run_terminal(&mut app);
+
+    // return the front panel to the user, even if the terminal failed,
+    // without reconnecting if the connection was lost
+    let local_res: io::Result<()> = match app.dp832.connection() {
+        Some(dp) if args.lock => dp.set_local(),
+        _ => Ok(()),
+    };
+
+    res?;
+    local_res?;
 
     Ok(())
 }
@@ -0,0 +1,56 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::Mutex,
+    time::Instant,
+};
+
+/// Logger writing every record to a file.
+///
+/// The terminal is used by the TUI, so logs can't be written to stderr.
+struct FileLogger {
+    start: Instant,
+    file: Mutex<BufWriter<File>>,
+}
+
+impl log::Log for FileLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let elapsed: f64 = self.start.elapsed().as_secs_f64();
+        if let Ok(mut file) = self.file.lock() {
+            // nowhere to report errors
+            let _ = writeln!(
+                file,
+                "{elapsed:>10.6} {:<5} {}: {}",
+                record.level(),
+                record.target(),
+                record.args()
+            );
+            // the logger is never dropped, flush every record so none are
+            // lost when exiting with an error
+            let _ = file.flush();
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Write all log records, including the SCPI transcript, to a file.
+pub fn init(path: &Path) -> anyhow::Result<()> {
+    let logger: FileLogger = FileLogger {
+        start: Instant::now(),
+        file: Mutex::new(BufWriter::new(File::create(path)?)),
+    };
+    log::set_logger(Box::leak(Box::new(logger)))
+        .map_err(|e| anyhow::anyhow!("Failed to set logger: {e}"))?;
+    log::set_max_level(log::LevelFilter::Trace);
+    Ok(())
+}